@@ -0,0 +1,534 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! ads.txt and app-ads.txt (IAB Tech Lab, version 1.1) parsing, and checking
+//! the sellers named by a bid request against a parsed file.
+//!
+//! Both files share the same format. Fetching them is left to the caller:
+//! `inventory` tells which file applies to a request (the site's ads.txt or
+//! the app's app-ads.txt), and `AdsTxt::check` matches the request against
+//! it.
+
+use std::fmt::{self, Display};
+
+use serde_json;
+
+use v2_5::{BidRequest, SupplyChain};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relationship {
+    // The publisher directly controls the account.
+    Direct,
+    // The publisher authorized another entity to control the account.
+    Reseller,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    // 1-based line number in the file.
+    pub line: usize,
+
+    // Canonical domain of the advertising system, lowercased.
+    pub domain: String,
+
+    // Publisher's account ID within the advertising system.
+    pub account_id: String,
+
+    pub relationship: Relationship,
+
+    // ID of the advertising system within a certification authority,
+    // e.g. the TAG-ID.
+    pub certification_authority_id: Option<String>,
+
+    // Anything after the first ';', unparsed.
+    pub extension: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Variable {
+    // Contact information of the file's owner.
+    Contact(String),
+    // A subdomain whose own file should be crawled as well.
+    Subdomain(String),
+    // Business domain of the owner of the inventory.
+    OwnerDomain(String),
+    // Domain of a sales house managing the inventory, optionally followed
+    // by ",<country code>".
+    ManagerDomain(String),
+    // Domain of a partner whose app-ads.txt lists CTV inventory sold on
+    // the publisher's behalf.
+    InventoryPartnerDomain(String),
+    // Any other NAME=value pair; the name is kept as written.
+    Other(String, String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Malformed {
+    // 1-based line number in the file.
+    pub line: usize,
+    // The line, without its comment.
+    pub text: String,
+    pub reason: String,
+}
+
+// A parsed file. Parsing never fails: lines that can't be read are kept in
+// `malformed` and otherwise ignored, as the spec asks crawlers to do.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AdsTxt {
+    pub records: Vec<Record>,
+    pub variables: Vec<Variable>,
+    pub malformed: Vec<Malformed>,
+}
+
+// Where the inventory of a request is declared: ads.txt on the site's
+// domain, or app-ads.txt on the developer domain of the app's store listing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Inventory<'a> {
+    Site(&'a str),
+    App(&'a str),
+}
+
+// The outcome of looking up one seller account in the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub domain: String,
+    pub account_id: String,
+    // The relationship the seller's position calls for.
+    pub expected: Option<Relationship>,
+    // The relationship listed in the file; `None` if the account isn't
+    // listed.
+    pub found: Option<Relationship>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Authorization {
+    // The exchange and the publisher ID of the site or app; `None` if the
+    // request has no publisher ID.
+    pub seller: Option<Check>,
+
+    // The first `SupplyChain` node, the only one that sells the
+    // publisher's inventory directly; in a complete chain it must be
+    // listed as DIRECT. The nodes after it are vouched for by the
+    // sellers.json of the node before them, not by this file.
+    pub first_node: Option<Check>,
+}
+
+impl Display for Relationship {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(match *self {
+            Relationship::Direct => "DIRECT",
+            Relationship::Reseller => "RESELLER",
+        })
+    }
+}
+
+impl Display for Malformed {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "line {}: {} ({:?})", self.line, self.reason, self.text)
+    }
+}
+
+impl AdsTxt {
+    pub fn parse(bytes: &[u8]) -> AdsTxt {
+        let text = String::from_utf8_lossy(bytes);
+        let text = text.trim_start_matches('\u{feff}');
+
+        let mut file = AdsTxt::default();
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let text = match line.find('#') {
+                Some(n) => &line[..n],
+                None => line,
+            }
+            .trim();
+            if text.is_empty() {
+                continue;
+            }
+
+            let result = if is_variable(text) {
+                parse_variable(text).map(|v| file.variables.push(v))
+            } else {
+                parse_record(text, line_no).map(|r| file.records.push(r))
+            };
+            if let Err(reason) = result {
+                file.malformed.push(Malformed {
+                    line: line_no,
+                    text: text.to_string(),
+                    reason,
+                });
+            }
+        }
+        file
+    }
+
+    // Records for an account at an advertising system. Domains compare
+    // case-insensitively, account IDs exactly.
+    pub fn find<'a>(&'a self, domain: &'a str, account_id: &'a str) -> impl Iterator<Item = &'a Record> {
+        self.records
+            .iter()
+            .filter(move |r| r.domain.eq_ignore_ascii_case(domain) && r.account_id == account_id)
+    }
+
+    // How the account is listed; DIRECT wins if it is listed both ways.
+    pub fn relationship(&self, domain: &str, account_id: &str) -> Option<Relationship> {
+        let mut found = None;
+        for record in self.find(domain, account_id) {
+            if record.relationship == Relationship::Direct {
+                return Some(Relationship::Direct);
+            }
+            found = Some(record.relationship);
+        }
+        found
+    }
+
+    pub fn contacts(&self) -> impl Iterator<Item = &str> {
+        self.variables.iter().filter_map(|v| match *v {
+            Variable::Contact(ref s) => Some(s.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn subdomains(&self) -> impl Iterator<Item = &str> {
+        self.variables.iter().filter_map(|v| match *v {
+            Variable::Subdomain(ref s) => Some(s.as_str()),
+            _ => None,
+        })
+    }
+
+    // The spec allows a single OWNERDOMAIN; the first one wins.
+    pub fn owner_domain(&self) -> Option<&str> {
+        self.variables.iter().find_map(|v| match *v {
+            Variable::OwnerDomain(ref s) => Some(s.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn manager_domains(&self) -> impl Iterator<Item = &str> {
+        self.variables.iter().filter_map(|v| match *v {
+            Variable::ManagerDomain(ref s) => Some(s.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn inventory_partner_domains(&self) -> impl Iterator<Item = &str> {
+        self.variables.iter().filter_map(|v| match *v {
+            Variable::InventoryPartnerDomain(ref s) => Some(s.as_str()),
+            _ => None,
+        })
+    }
+
+    // Checks the sellers named by `request` against this file, which must
+    // be the one for `inventory(request)`. `exchange_domain` is the
+    // advertising system the request was received from; it is paired with
+    // the site's or app's `publisher.id`. Fails only if `source.ext.schain`
+    // is malformed.
+    pub fn check<E>(&self, request: &BidRequest<E>, exchange_domain: &str) -> Result<Authorization, serde_json::Error> {
+        let publisher = request
+            .site
+            .as_ref()
+            .and_then(|s| s.publisher.as_ref())
+            .or_else(|| request.app.as_ref().and_then(|a| a.publisher.as_ref()));
+        let seller = publisher.map(|p| Check {
+            domain: exchange_domain.to_ascii_lowercase(),
+            account_id: p.id.clone(),
+            expected: None,
+            found: self.relationship(exchange_domain, &p.id),
+        });
+
+        let schain = match request.source.as_ref().and_then(|s| s.supply_chain()) {
+            Some(schain) => Some(schain?),
+            None => None,
+        };
+        let first_node = schain.and_then(|s| self.check_first_node(&s));
+
+        Ok(Authorization { seller, first_node })
+    }
+
+    fn check_first_node(&self, schain: &SupplyChain) -> Option<Check> {
+        schain.nodes.first().map(|node| Check {
+            domain: node.asi.to_ascii_lowercase(),
+            account_id: node.sid.clone(),
+            // The first known node of an incomplete chain may sit anywhere
+            // in the real chain.
+            expected: if schain.complete {
+                Some(Relationship::Direct)
+            } else {
+                None
+            },
+            found: self.relationship(&node.asi, &node.sid),
+        })
+    }
+}
+
+// The file that declares who may sell the request's inventory. The
+// request doesn't carry the app's store listing, so the developer domain
+// is taken from app.publisher.domain, else app.domain.
+pub fn inventory<E>(request: &BidRequest<E>) -> Option<Inventory<'_>> {
+    if let Some(domain) = request.site.as_ref().and_then(|s| s.domain.as_ref()) {
+        return Some(Inventory::Site(domain));
+    }
+    let app = request.app.as_ref()?;
+    app.publisher
+        .as_ref()
+        .and_then(|p| p.domain.as_ref())
+        .or(app.domain.as_ref())
+        .map(|domain| Inventory::App(domain))
+}
+
+impl Check {
+    pub fn is_listed(&self) -> bool {
+        self.found.is_some()
+    }
+
+    // Listed, and with the relationship the seller's position calls for.
+    pub fn is_authorized(&self) -> bool {
+        match (self.expected, self.found) {
+            (_, None) => false,
+            (None, Some(_)) => true,
+            (Some(expected), Some(found)) => expected == found,
+        }
+    }
+}
+
+impl Authorization {
+    // Every seller the request names is authorized, and there is at least
+    // one.
+    pub fn is_authorized(&self) -> bool {
+        (self.seller.is_some() || self.first_node.is_some())
+            && self.seller.iter().chain(&self.first_node).all(Check::is_authorized)
+    }
+
+    // DIRECT if every seller is listed as DIRECT, RESELLER if any is a
+    // reseller, `None` if the request isn't authorized.
+    pub fn relationship(&self) -> Option<Relationship> {
+        if !self.is_authorized() {
+            return None;
+        }
+        if self
+            .seller
+            .iter()
+            .chain(&self.first_node)
+            .any(|c| c.found == Some(Relationship::Reseller))
+        {
+            Some(Relationship::Reseller)
+        } else {
+            Some(Relationship::Direct)
+        }
+    }
+}
+
+// A variable's name can't contain a comma, so a '=' before the first comma
+// (or without any comma) marks a variable.
+fn is_variable(text: &str) -> bool {
+    match (text.find('='), text.find(',')) {
+        (Some(eq), Some(comma)) => eq < comma,
+        (Some(_), None) => true,
+        _ => false,
+    }
+}
+
+fn parse_variable(text: &str) -> Result<Variable, String> {
+    let eq = text.find('=').unwrap_or(text.len());
+    let name = text[..eq].trim();
+    let value = text[eq + 1..].trim().to_string();
+    if name.is_empty() {
+        return Err("empty variable name".to_string());
+    }
+    if value.is_empty() {
+        return Err(format!("empty value for {}", name));
+    }
+
+    Ok(match name.to_ascii_uppercase().as_str() {
+        "CONTACT" => Variable::Contact(value),
+        "SUBDOMAIN" => Variable::Subdomain(value.to_ascii_lowercase()),
+        "OWNERDOMAIN" => Variable::OwnerDomain(value.to_ascii_lowercase()),
+        // "manager.com,US": the domain, then an optional country code.
+        "MANAGERDOMAIN" => Variable::ManagerDomain(match value.find(',') {
+            Some(i) => format!("{}{}", value[..i].to_ascii_lowercase(), &value[i..]),
+            None => value.to_ascii_lowercase(),
+        }),
+        "INVENTORYPARTNERDOMAIN" => Variable::InventoryPartnerDomain(value.to_ascii_lowercase()),
+        _ => Variable::Other(name.to_string(), value),
+    })
+}
+
+fn parse_record(text: &str, line: usize) -> Result<Record, String> {
+    let (fields, extension) = match text.find(';') {
+        Some(n) => (&text[..n], Some(text[n + 1..].trim().to_string())),
+        None => (text, None),
+    };
+    let fields: Vec<&str> = fields.split(',').map(str::trim).collect();
+    if fields.len() < 3 || fields.len() > 4 {
+        return Err(format!("expected 3 or 4 fields, got {}", fields.len()));
+    }
+    if fields[0].is_empty() || fields[0].contains(char::is_whitespace) {
+        return Err(format!("invalid advertising system domain {:?}", fields[0]));
+    }
+    if fields[1].is_empty() {
+        return Err("empty account ID".to_string());
+    }
+    let relationship = match fields[2].to_ascii_uppercase().as_str() {
+        "DIRECT" => Relationship::Direct,
+        "RESELLER" => Relationship::Reseller,
+        _ => return Err(format!("unknown relationship {:?}", fields[2])),
+    };
+
+    Ok(Record {
+        line,
+        domain: fields[0].to_ascii_lowercase(),
+        account_id: fields[1].to_string(),
+        relationship,
+        certification_authority_id: fields.get(3).filter(|s| !s.is_empty()).map(|s| s.to_string()),
+        extension: extension.filter(|s| !s.is_empty()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use v2_5::{Source, SupplyChainNode};
+
+    const FILE: &str = "\u{feff}# ads.txt for example.com\r
+CONTACT=adops@example.com\r
+OWNERDOMAIN=Example.com\r
+MANAGERDOMAIN=Manager.com,US\r
+\r
+exchange1.com, 8953, DIRECT, f08c47fec0942fa0 # main account\r
+Exchange2.com, 8953, RESELLER;region=eu\r
+exchange3.com, pub-1, reseller, \r
+exchange3.com, pub-1, DIRECT\r
+exchange4.com, 12\r
+exchange5.com, 13, PARTNER\r
+subdomain=news.example.com\r
+";
+
+    fn request(schain: Option<SupplyChain>) -> BidRequest {
+        let mut b: BidRequest = serde_json::from_str(
+            r#"{
+                "id": "1234",
+                "imp": [],
+                "at": 1,
+                "site": {"domain": "example.com", "publisher": {"id": "8953"}}
+            }"#,
+        )
+        .unwrap();
+        if let Some(schain) = schain {
+            let mut source: Source = serde_json::from_str("{}").unwrap();
+            source.set_supply_chain(&schain).unwrap();
+            b.source = Some(source);
+        }
+        b
+    }
+
+    #[test]
+    fn parse() {
+        let file = AdsTxt::parse(FILE.as_bytes());
+
+        assert_eq!(4, file.records.len());
+        assert_eq!(
+            Record {
+                line: 6,
+                domain: "exchange1.com".to_string(),
+                account_id: "8953".to_string(),
+                relationship: Relationship::Direct,
+                certification_authority_id: Some("f08c47fec0942fa0".to_string()),
+                extension: None,
+            },
+            file.records[0]
+        );
+        assert_eq!("exchange2.com", file.records[1].domain);
+        assert_eq!(Some("region=eu".to_string()), file.records[1].extension);
+        assert_eq!(None, file.records[2].certification_authority_id);
+
+        assert_eq!(vec!["adops@example.com"], file.contacts().collect::<Vec<_>>());
+        assert_eq!(Some("example.com"), file.owner_domain());
+        assert_eq!(vec!["manager.com,US"], file.manager_domains().collect::<Vec<_>>());
+        assert_eq!(vec!["news.example.com"], file.subdomains().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn malformed_lines() {
+        let file = AdsTxt::parse(FILE.as_bytes());
+
+        let lines: Vec<_> = file.malformed.iter().map(|m| m.line).collect();
+        assert_eq!(vec![10, 11], lines);
+        assert!(file.malformed[1].reason.contains("PARTNER"));
+    }
+
+    #[test]
+    fn relationship_prefers_direct() {
+        let file = AdsTxt::parse(FILE.as_bytes());
+
+        assert_eq!(Some(Relationship::Direct), file.relationship("EXCHANGE1.com", "8953"));
+        assert_eq!(Some(Relationship::Reseller), file.relationship("exchange2.com", "8953"));
+        assert_eq!(None, file.relationship("exchange1.com", "89531"));
+    }
+
+    #[test]
+    fn check_without_schain() {
+        let file = AdsTxt::parse(FILE.as_bytes());
+        let b = request(None);
+
+        assert_eq!(Some(Inventory::Site("example.com")), inventory(&b));
+
+        let auth = file.check(&b, "exchange2.com").unwrap();
+        assert_eq!(Some(Relationship::Reseller), auth.relationship());
+
+        let auth = file.check(&b, "unknown.com").unwrap();
+        assert!(!auth.is_authorized());
+        assert_eq!(None, auth.relationship());
+    }
+
+    #[test]
+    fn app_inventory() {
+        let mut b: BidRequest = serde_json::from_str(
+            r#"{
+                "id": "1234",
+                "imp": [],
+                "at": 1,
+                "app": {"id": "a1", "domain": "game.example.com", "publisher": {"id": "p1", "domain": "example.com"}}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(Some(Inventory::App("example.com")), inventory(&b));
+        b.app.as_mut().unwrap().publisher = None;
+        assert_eq!(Some(Inventory::App("game.example.com")), inventory(&b));
+        b.app.as_mut().unwrap().domain = None;
+        assert_eq!(None, inventory(&b));
+    }
+
+    #[test]
+    fn check_schain() {
+        let file = AdsTxt::parse(FILE.as_bytes());
+        let mut schain = SupplyChain::new(true);
+        schain
+            .append_node(SupplyChainNode::new("exchange1.com".to_string(), "8953".to_string()))
+            .unwrap();
+        schain
+            .append_node(SupplyChainNode::new("exchange2.com".to_string(), "8953".to_string()))
+            .unwrap();
+
+        let auth = file.check(&request(Some(schain.clone())), "exchange2.com").unwrap();
+        assert!(auth.is_authorized());
+        assert_eq!(Some(Relationship::Reseller), auth.relationship());
+
+        // Nodes after the first are for sellers.json to vouch for.
+        let mut longer = schain.clone();
+        longer
+            .append_node(SupplyChainNode::new("unknown.com".to_string(), "1".to_string()))
+            .unwrap();
+        assert!(file.check(&request(Some(longer)), "exchange2.com").unwrap().is_authorized());
+
+        // A reseller can't be the first node of a complete chain.
+        schain.nodes.swap(0, 1);
+        let auth = file.check(&request(Some(schain)), "exchange2.com").unwrap();
+        assert!(!auth.is_authorized());
+        assert!(auth.first_node.unwrap().is_listed());
+    }
+}
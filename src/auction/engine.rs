@@ -0,0 +1,656 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashSet;
+
+use super::{Macros, PriceCodec};
+use currency::{self, CurrencyConverter};
+use v2_5::{AuctionType, Bid, BidRequest, BidResponse, Deal, Imp, LossReason, Price, SeatBid};
+
+// A deal's `at` value saying its bid floor is the price.
+const FIXED_PRICE_DEAL: u32 = 3;
+
+// The exchange-specific loss reason of the bids of a seat bid with group
+// set that didn't win all its impressions.
+pub const GROUP_NOT_WON: LossReason = LossReason::ExchangeSpecific(1000);
+
+// How a winning bid's clearing price is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pricing {
+    FirstPrice,
+    SecondPricePlus,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Auction {
+    // The currency floors and bids are converted to before they're compared.
+    pub currency: String,
+    // Added to the second price under second price plus auctions, in
+    // `currency`.
+    pub increment: Price,
+    // How exchange-specific auction types clear.
+    pub exchange_specific: Pricing,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Win<'a> {
+    pub imp: &'a Imp,
+    pub response: &'a BidResponse,
+    pub seat_bid: &'a SeatBid,
+    pub bid: &'a Bid,
+    // The clearing price, in the bid's currency.
+    pub price: Price,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loss<'a> {
+    // None if the bid is for an impression the request doesn't have.
+    pub imp: Option<&'a Imp>,
+    pub response: &'a BidResponse,
+    pub seat_bid: &'a SeatBid,
+    pub bid: &'a Bid,
+    pub reason: LossReason,
+    // The clearing price of the impression's winner in the bid's currency,
+    // or 0 when nobody won or the bid's currency couldn't be converted.
+    pub price: Price,
+}
+
+// A loss notice for a bidder: its lurl with the macros expanded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LossNotice<'a> {
+    pub bid: &'a Bid,
+    pub reason: LossReason,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Outcome<'a> {
+    // At most one per impression, in the order of the impressions.
+    pub winners: Vec<Win<'a>>,
+    // Every other bid, in the order of the responses.
+    pub losses: Vec<Loss<'a>>,
+}
+
+struct Candidate<'a> {
+    response: &'a BidResponse,
+    seat_bid: &'a SeatBid,
+    bid: &'a Bid,
+    // The bid price in the auction currency, if it converts.
+    price: Option<Price>,
+    // The floor the bid has to meet, in the auction currency.
+    floor: Option<Price>,
+    // The index of the seat bid among all seat bids, for the group rule.
+    group: usize,
+    imp: Option<&'a Imp>,
+    deal: Option<&'a Deal>,
+    rejected: Option<LossReason>,
+}
+
+impl Default for Auction {
+    fn default() -> Auction {
+        Auction {
+            currency: currency::DEFAULT.to_string(),
+            increment: Price::from_micros(10_000),
+            exchange_specific: Pricing::FirstPrice,
+        }
+    }
+}
+
+impl<'a> Outcome<'a> {
+    pub fn winner(&self, imp_id: &str) -> Option<&Win<'a>> {
+        self.winners.iter().find(|w| w.imp.id == imp_id)
+    }
+
+    // The notices for the losing bids that gave a lurl. ${AUCTION_PRICE}
+    // is the clearing price of the impression's winner, or 0 when nobody
+    // won, encoded with `codec` if given.
+    pub fn loss_notices(&self, codec: Option<&dyn PriceCodec>) -> Vec<LossNotice<'a>> {
+        let mut notices = vec![];
+        for loss in &self.losses {
+            let lurl = match loss.bid.lurl {
+                Some(ref lurl) => lurl,
+                None => continue,
+            };
+            let mut macros =
+                Macros::new(loss.response, loss.seat_bid, loss.bid, loss.price).with_loss(loss.reason.clone());
+            if let Some(codec) = codec {
+                macros = macros.with_price_codec(codec);
+            }
+            notices.push(LossNotice {
+                bid: loss.bid,
+                reason: loss.reason.clone(),
+                url: macros.expand(lurl).into_owned(),
+            });
+        }
+        notices
+    }
+}
+
+impl Auction {
+    // Picks a winner for every impression of `request` among the bids of
+    // `responses`. Floors and bids are compared in the auction currency,
+    // through `converter`, and clearing prices given back in the currency
+    // of each bid.
+    pub fn run<'a, E>(
+        &self,
+        request: &'a BidRequest<E>,
+        responses: &'a [BidResponse],
+        converter: &dyn CurrencyConverter,
+    ) -> Outcome<'a> {
+        let mut candidates = vec![];
+        let mut group = 0;
+        for response in responses {
+            for seat_bid in &response.seat_bid {
+                for bid in &seat_bid.bid {
+                    let imp = request.imp.iter().find(|i| i.id == bid.imp_id);
+                    let price = converter
+                        .convert(bid.price, response.effective_currency(), &self.currency)
+                        .ok();
+                    let (deal, floor, rejected) =
+                        match self.check(request, response, seat_bid, bid, price, imp, converter) {
+                            Ok((deal, floor)) => (deal, floor, None),
+                            Err(reason) => (None, None, Some(reason)),
+                        };
+                    candidates.push(Candidate {
+                        response,
+                        seat_bid,
+                        bid,
+                        price,
+                        floor,
+                        group,
+                        imp,
+                        deal,
+                        rejected,
+                    });
+                }
+                group += 1;
+            }
+        }
+
+        // A seat bid with group set wins all its bids or none, so a group
+        // that misses one is taken out and the auction run again.
+        let mut excluded = HashSet::new();
+        let winners = loop {
+            let winners: Vec<Option<usize>> = request
+                .imp
+                .iter()
+                .map(|imp| best(&candidates, &excluded, imp, None))
+                .collect();
+            let before = excluded.len();
+            for c in &candidates {
+                if c.seat_bid.group == Some(1) && !excluded.contains(&c.group) {
+                    let won = winners.iter().any(|w| w.is_some_and(|w| std::ptr::eq(candidates[w].bid, c.bid)));
+                    if !won {
+                        excluded.insert(c.group);
+                    }
+                }
+            }
+            if excluded.len() == before {
+                break winners;
+            }
+        };
+
+        // The clearing prices in the auction currency, by impression.
+        let prices: Vec<Option<Price>> = winners
+            .iter()
+            .map(|w| w.map(|w| self.clearing_price(request, &candidates, &excluded, w)))
+            .collect();
+
+        let mut outcome = Outcome::default();
+        for ((imp, winner), price) in request.imp.iter().zip(&winners).zip(&prices) {
+            if let (Some(w), Some(price)) = (*winner, *price) {
+                let c = &candidates[w];
+                outcome.winners.push(Win {
+                    imp,
+                    response: c.response,
+                    seat_bid: c.seat_bid,
+                    bid: c.bid,
+                    price: self.in_bid_currency(c, price, converter),
+                });
+            }
+        }
+        for (i, c) in candidates.iter().enumerate() {
+            let index = c
+                .imp
+                .and_then(|imp| request.imp.iter().position(|i| std::ptr::eq(i, imp)));
+            let winner = index.and_then(|index| winners[index]);
+            if winner == Some(i) {
+                continue;
+            }
+            let reason = c.rejected.clone().unwrap_or_else(|| match winner {
+                _ if excluded.contains(&c.group) => GROUP_NOT_WON,
+                Some(w) if candidates[w].deal.is_some() && c.deal.is_none() => LossReason::LostToPmpDeal,
+                _ => LossReason::LostToHigherBid,
+            });
+            let price = index
+                .and_then(|index| prices[index])
+                .map_or(Price::ZERO, |p| self.in_bid_currency(c, p, converter));
+            outcome.losses.push(Loss {
+                imp: c.imp,
+                response: c.response,
+                seat_bid: c.seat_bid,
+                bid: c.bid,
+                reason,
+                price,
+            });
+        }
+        outcome
+    }
+
+    fn clearing_price<E>(
+        &self,
+        request: &BidRequest<E>,
+        candidates: &[Candidate],
+        excluded: &HashSet<usize>,
+        winner: usize,
+    ) -> Price {
+        let c = &candidates[winner];
+        let bid_price = c.price.unwrap_or(c.bid.price);
+        let auction_type = c
+            .deal
+            .and_then(|d| d.auction_type.as_ref())
+            .unwrap_or(&request.auction_type);
+        let pricing = match *auction_type {
+            AuctionType::FirstPrice => Pricing::FirstPrice,
+            AuctionType::SecondPricePlus => Pricing::SecondPricePlus,
+            AuctionType::ExchangeSpecific(FIXED_PRICE_DEAL) if c.deal.is_some() => {
+                return c.floor.unwrap_or(bid_price);
+            }
+            AuctionType::ExchangeSpecific(_) => self.exchange_specific,
+        };
+        match pricing {
+            Pricing::FirstPrice => bid_price,
+            Pricing::SecondPricePlus => {
+                let second = c
+                    .imp
+                    .and_then(|imp| best(candidates, excluded, imp, Some(winner)))
+                    .and_then(|s| candidates[s].price)
+                    .unwrap_or(Price::ZERO);
+                (second.max(c.floor.unwrap_or(Price::ZERO)) + self.increment).min(bid_price)
+            }
+        }
+    }
+
+    // `price`, in the auction currency, in the currency of the bid.
+    fn in_bid_currency(&self, c: &Candidate, price: Price, converter: &dyn CurrencyConverter) -> Price {
+        converter
+            .convert(price, &self.currency, c.response.effective_currency())
+            .unwrap_or(Price::ZERO)
+    }
+
+    // The deal a bid is for and the floor it met, in the auction currency,
+    // or why it can't take part. `price` is the bid price converted.
+    #[allow(clippy::too_many_arguments)]
+    fn check<'a, E>(
+        &self,
+        request: &BidRequest<E>,
+        response: &BidResponse,
+        seat_bid: &SeatBid,
+        bid: &Bid,
+        price: Option<Price>,
+        imp: Option<&'a Imp>,
+        converter: &dyn CurrencyConverter,
+    ) -> Result<(Option<&'a Deal>, Option<Price>), LossReason> {
+        if response.id != request.id {
+            return Err(LossReason::InvalidAuctionId);
+        }
+        let imp = imp.ok_or(LossReason::InvalidBidResponse)?;
+        if bid.price <= Price::ZERO {
+            return Err(LossReason::MissingBidPrice);
+        }
+        if !response.currency_allowed_by(request) {
+            return Err(LossReason::InvalidBidResponse);
+        }
+        let price = price.ok_or(LossReason::InvalidBidResponse)?;
+
+        let seat = seat_bid.seat.as_deref();
+        let allowed = |list: &[String]| list.is_empty() || seat.is_some_and(|s| list.iter().any(|w| w == s));
+        if !allowed(&request.seat_whitelist) || seat.is_some_and(|s| request.seat_blocklist.iter().any(|b| b == s)) {
+            return Err(LossReason::BuyerSeatBlocked);
+        }
+        let blocked_domain = bid
+            .adomain
+            .iter()
+            .any(|d| request.advertiser_blocklist.iter().any(|b| domain_matches(d, b)));
+        if blocked_domain {
+            return Err(LossReason::CreativeFilteredAdvertiserExclusions);
+        }
+        if bid.bundle.as_ref().is_some_and(|b| request.app_blocklist.contains(b)) {
+            return Err(LossReason::CreativeFilteredAppBundleExclusions);
+        }
+        if bid
+            .cat
+            .iter()
+            .any(|c| request.category_blocklist.iter().any(|b| c.is_within(b)))
+        {
+            return Err(LossReason::CreativeFilteredCategoryExclusions);
+        }
+
+        let pmp = imp.pmp.as_ref();
+        let deal = match bid.deal_id {
+            Some(ref id) => Some(pmp.and_then(|p| p.deal(id)).ok_or(LossReason::InvalidDealId)?),
+            None if pmp.is_some_and(|p| p.private_auction) => return Err(LossReason::InvalidDealId),
+            None => None,
+        };
+        let (floor, below_floor) = match deal {
+            Some(deal) => {
+                if !allowed(&deal.seat_whitelist) {
+                    return Err(LossReason::BuyerSeatBlocked);
+                }
+                let allowed_domains = deal.advertiser_whitelist.is_empty()
+                    || (!bid.adomain.is_empty()
+                        && bid
+                            .adomain
+                            .iter()
+                            .all(|d| deal.advertiser_whitelist.iter().any(|w| domain_matches(d, w))));
+                if !allowed_domains {
+                    return Err(LossReason::CreativeFilteredNotAllowedInPmpDeal);
+                }
+                (deal.floor_in(&self.currency, converter), LossReason::BelowDealFloor)
+            }
+            None => (imp.floor_in(&self.currency, converter), LossReason::BelowAuctionFloor),
+        };
+        // A floor the exchange can't convert is its own failure, not the
+        // bidder's.
+        let floor = floor.map_err(|_| LossReason::InternalError)?;
+        if floor.is_some_and(|floor| price < floor) {
+            return Err(below_floor);
+        }
+        Ok((deal, floor))
+    }
+}
+
+// The highest eligible bid for `imp`; the earliest wins a tie.
+fn best(candidates: &[Candidate], excluded: &HashSet<usize>, imp: &Imp, skip: Option<usize>) -> Option<usize> {
+    let mut best: Option<usize> = None;
+    for (i, c) in candidates.iter().enumerate() {
+        let eligible = c.rejected.is_none()
+            && !excluded.contains(&c.group)
+            && Some(i) != skip
+            && c.imp.is_some_and(|i| std::ptr::eq(i, imp));
+        if eligible && best.is_none_or(|b| c.price > candidates[b].price) {
+            best = Some(i);
+        }
+    }
+    best
+}
+
+// `domain` is `listed` or one of its subdomains, ignoring case.
+fn domain_matches(domain: &str, listed: &str) -> bool {
+    let (domain, listed) = (domain.as_bytes(), listed.as_bytes());
+    domain.len() >= listed.len()
+        && domain[domain.len() - listed.len()..].eq_ignore_ascii_case(listed)
+        && (domain.len() == listed.len() || domain[domain.len() - listed.len() - 1] == b'.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use currency::RateTable;
+    use serde_json;
+
+    const REQUEST: &str = r#"{
+        "id": "req-1",
+        "at": 2,
+        "imp": [
+            {"id": "1", "bidfloor": 0.5},
+            {"id": "2", "pmp": {"private_auction": 1, "deals": [
+                {"id": "fixed", "bidfloor": 3.0, "at": 3},
+                {"id": "d2", "bidfloor": 2.0, "wseat": ["s3"]}
+            ]}},
+            {"id": "3"}
+        ],
+        "bseat": ["blocked"],
+        "badv": ["bad.com"],
+        "bcat": ["IAB25"]
+    }"#;
+
+    fn request() -> BidRequest {
+        serde_json::from_str(REQUEST).unwrap()
+    }
+
+    fn response(json: &str) -> BidResponse {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn price(s: &str) -> Price {
+        s.parse().unwrap()
+    }
+
+    fn reasons<'a>(outcome: &Outcome<'a>) -> Vec<(&'a str, LossReason)> {
+        outcome
+            .losses
+            .iter()
+            .map(|l| (l.bid.id.as_str(), l.reason.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn second_price_with_floors_and_filters() {
+        let request = request();
+        let responses = vec![
+            response(
+                r#"{"id": "req-1", "seatbid": [{"seat": "s1", "bid": [
+                    {"id": "a", "impid": "1", "price": 2.0},
+                    {"id": "b", "impid": "1", "price": 0.25},
+                    {"id": "c", "impid": "1", "price": 9.0, "adomain": ["shop.bad.com"]},
+                    {"id": "d", "impid": "1", "price": 9.0, "cat": ["IAB25-3"]},
+                    {"id": "e", "impid": "9", "price": 9.0}
+                ]}]}"#,
+            ),
+            response(
+                r#"{"id": "req-1", "seatbid": [
+                    {"seat": "s2", "bid": [{"id": "f", "impid": "1", "price": 1.5}]},
+                    {"seat": "blocked", "bid": [{"id": "g", "impid": "1", "price": 9.0}]}
+                ]}"#,
+            ),
+            response(r#"{"id": "other", "seatbid": [{"bid": [{"id": "h", "impid": "1", "price": 9.0}]}]}"#),
+        ];
+        let outcome = Auction::default().run(&request, &responses, &RateTable::default());
+
+        let win = outcome.winner("1").unwrap();
+        assert_eq!("a", win.bid.id);
+        assert_eq!(price("1.51"), win.price);
+        assert_eq!(1, outcome.winners.len());
+        assert_eq!(
+            vec![
+                ("b", LossReason::BelowAuctionFloor),
+                ("c", LossReason::CreativeFilteredAdvertiserExclusions),
+                ("d", LossReason::CreativeFilteredCategoryExclusions),
+                ("e", LossReason::InvalidBidResponse),
+                ("f", LossReason::LostToHigherBid),
+                ("g", LossReason::BuyerSeatBlocked),
+                ("h", LossReason::InvalidAuctionId),
+            ],
+            reasons(&outcome)
+        );
+    }
+
+    #[test]
+    fn deals_and_private_auction() {
+        let request = request();
+        let responses = vec![response(
+            r#"{"id": "req-1", "seatbid": [
+                {"seat": "s1", "bid": [
+                    {"id": "open", "impid": "2", "price": 9.0},
+                    {"id": "fixed", "impid": "2", "price": 4.0, "dealid": "fixed"},
+                    {"id": "unknown", "impid": "2", "price": 9.0, "dealid": "nope"},
+                    {"id": "seat", "impid": "2", "price": 9.0, "dealid": "d2"}
+                ]},
+                {"seat": "s3", "bid": [{"id": "low", "impid": "2", "price": 1.0, "dealid": "d2"}]}
+            ]}"#,
+        )];
+        let outcome = Auction::default().run(&request, &responses, &RateTable::default());
+
+        let win = outcome.winner("2").unwrap();
+        assert_eq!("fixed", win.bid.id);
+        assert_eq!(price("3"), win.price);
+        assert_eq!(
+            vec![
+                ("open", LossReason::InvalidDealId),
+                ("unknown", LossReason::InvalidDealId),
+                ("seat", LossReason::BuyerSeatBlocked),
+                ("low", LossReason::BelowDealFloor),
+            ],
+            reasons(&outcome)
+        );
+    }
+
+    #[test]
+    fn first_price_and_pmp_losses() {
+        let mut request = request();
+        request.auction_type = AuctionType::FirstPrice;
+        request.imp[1].pmp.as_mut().unwrap().private_auction = false;
+        let responses = vec![response(
+            r#"{"id": "req-1", "seatbid": [{"seat": "s1", "bid": [
+                {"id": "open", "impid": "2", "price": 2.5},
+                {"id": "fixed", "impid": "2", "price": 4.0, "dealid": "fixed"},
+                {"id": "lone", "impid": "3", "price": 0.75}
+            ]}]}"#,
+        )];
+        let outcome = Auction::default().run(&request, &responses, &RateTable::default());
+
+        assert_eq!(price("3"), outcome.winner("2").unwrap().price);
+        assert_eq!(price("0.75"), outcome.winner("3").unwrap().price);
+        assert_eq!(vec![("open", LossReason::LostToPmpDeal)], reasons(&outcome));
+    }
+
+    #[test]
+    fn group_is_all_or_nothing() {
+        let request = request();
+        let responses = vec![response(
+            r#"{"id": "req-1", "seatbid": [
+                {"seat": "s1", "group": 1, "bid": [
+                    {"id": "g1", "impid": "1", "price": 5.0},
+                    {"id": "g3", "impid": "3", "price": 1.0}
+                ]},
+                {"seat": "s2", "bid": [
+                    {"id": "o1", "impid": "1", "price": 2.0},
+                    {"id": "o3", "impid": "3", "price": 3.0}
+                ]}
+            ]}"#,
+        )];
+        let outcome = Auction::default().run(&request, &responses, &RateTable::default());
+
+        assert_eq!("o1", outcome.winner("1").unwrap().bid.id);
+        // The group is gone, so nothing is left to set a second price.
+        assert_eq!(price("0.51"), outcome.winner("1").unwrap().price);
+        assert_eq!("o3", outcome.winner("3").unwrap().bid.id);
+        assert_eq!(vec![("g1", GROUP_NOT_WON), ("g3", GROUP_NOT_WON)], reasons(&outcome));
+    }
+
+    #[test]
+    fn loss_notices() {
+        let request = request();
+        let responses = vec![response(
+            r#"{"id": "req-1", "seatbid": [{"seat": "s1", "bid": [
+                {"id": "a", "impid": "1", "price": 2.0},
+                {"id": "b", "impid": "1", "price": 1.0, "lurl": "https://dsp.example/l?r=${AUCTION_LOSS}&p=${AUCTION_PRICE}"},
+                {"id": "c", "impid": "1", "price": 0.1, "lurl": "https://dsp.example/l?r=${AUCTION_LOSS}"},
+                {"id": "d", "impid": "1", "price": 0.1}
+            ]}]}"#,
+        )];
+        let outcome = Auction::default().run(&request, &responses, &RateTable::default());
+        let notices = outcome.loss_notices(None);
+
+        assert_eq!(2, notices.len());
+        assert_eq!("b", notices[0].bid.id);
+        assert_eq!(LossReason::LostToHigherBid, notices[0].reason);
+        assert_eq!("https://dsp.example/l?r=102&p=1.01", notices[0].url);
+        assert_eq!("https://dsp.example/l?r=100", notices[1].url);
+    }
+
+    #[test]
+    fn exchange_specific_pricing() {
+        let mut request = request();
+        request.auction_type = AuctionType::ExchangeSpecific(500);
+        let responses = vec![response(
+            r#"{"id": "req-1", "seatbid": [{"bid": [
+                {"id": "a", "impid": "3", "price": 2.0},
+                {"id": "b", "impid": "3", "price": 1.0}
+            ]}]}"#,
+        )];
+
+        assert_eq!(
+            price("2"),
+            Auction::default()
+                .run(&request, &responses, &RateTable::default())
+                .winners[0]
+                .price
+        );
+        let auction = Auction {
+            increment: price("0.5"),
+            exchange_specific: Pricing::SecondPricePlus,
+            ..Auction::default()
+        };
+        assert_eq!(
+            price("1.5"),
+            auction.run(&request, &responses, &RateTable::default()).winners[0].price
+        );
+    }
+
+    #[test]
+    fn bid_at_the_floor() {
+        let mut request = request();
+        request.imp[0].bid_floor = Some(price("0.1"));
+        let responses = vec![response(
+            r#"{"id": "req-1", "seatbid": [{"bid": [
+                {"id": "a", "impid": "1", "price": 0.1},
+                {"id": "b", "impid": "1", "price": 0.099999}
+            ]}]}"#,
+        )];
+        let outcome = Auction::default().run(&request, &responses, &RateTable::default());
+
+        assert_eq!(price("0.1"), outcome.winner("1").unwrap().price);
+        assert_eq!(vec![("b", LossReason::BelowAuctionFloor)], reasons(&outcome));
+    }
+
+    #[test]
+    fn currencies() {
+        let mut request = request();
+        request.currency = vec!["USD".to_string(), "EUR".to_string(), "CHF".to_string()];
+        request.imp[0].bid_floor_cur = Some("EUR".to_string());
+        request.imp[2].bid_floor = Some(price("1"));
+        let mut rates = RateTable::default();
+        rates.insert("USD", "EUR", 0.5);
+        let responses = vec![
+            response(
+                r#"{"id": "req-1", "cur": "EUR", "seatbid": [{"seat": "s1", "bid": [
+                    {"id": "a", "impid": "1", "price": 2.0},
+                    {"id": "b", "impid": "3", "price": 0.4}
+                ]}]}"#,
+            ),
+            response(
+                r#"{"id": "req-1", "seatbid": [{"seat": "s2", "bid": [
+                    {"id": "c", "impid": "1", "price": 3.0},
+                    {"id": "d", "impid": "1", "price": 0.9}
+                ]}]}"#,
+            ),
+            response(
+                r#"{"id": "req-1", "cur": "CHF", "seatbid": [{"bid": [{"id": "e", "impid": "1", "price": 9.0}]}]}"#,
+            ),
+        ];
+        let outcome = Auction::default().run(&request, &responses, &rates);
+
+        // 2 EUR, worth 4 USD, beats 3 USD and clears at 3.01 USD, given
+        // back as EUR; the floor of 0.5 EUR is 1 USD.
+        let win = outcome.winner("1").unwrap();
+        assert_eq!("a", win.bid.id);
+        assert_eq!(price("1.505"), win.price);
+        assert_eq!(
+            vec![
+                ("b", LossReason::BelowAuctionFloor),
+                ("c", LossReason::LostToHigherBid),
+                ("d", LossReason::BelowAuctionFloor),
+                ("e", LossReason::InvalidBidResponse),
+            ],
+            reasons(&outcome)
+        );
+        assert_eq!(price("3.01"), outcome.losses[1].price);
+    }
+}
@@ -0,0 +1,18 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The exchange's auction over the bids collected for a request, with
+//! notice macro substitution and clearing price encoding.
+
+mod engine;
+pub mod price_codec;
+mod substitution;
+
+pub use self::engine::{Auction, Loss, LossNotice, Outcome, Pricing, Win, GROUP_NOT_WON};
+pub use self::price_codec::PriceCodec;
+pub use self::substitution::Macros;
@@ -0,0 +1,260 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Encodings of the clearing price carried by `${AUCTION_PRICE}`: the
+//! exchange encodes it, the bidder decodes it from the win notice.
+
+use std::convert::TryFrom;
+use std::error;
+use std::fmt::{self, Display};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+use base64;
+use v2_5::Price;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    Base64,
+    // The decoded value has the wrong number of bytes.
+    Length(usize),
+    // The integrity signature doesn't match; the price was tampered with or
+    // the keys are wrong.
+    Signature,
+    InvalidPrice(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Base64 => formatter.write_str("invalid base64"),
+            Error::Length(n) => write!(formatter, "encrypted price has {} bytes, expected 28", n),
+            Error::Signature => formatter.write_str("encrypted price signature mismatch"),
+            Error::InvalidPrice(ref s) => write!(formatter, "invalid price {:?}", s),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+pub trait PriceCodec {
+    fn encode(&self, price: Price) -> String;
+    fn decode(&self, encoded: &str) -> Result<Price, Error>;
+}
+
+// The price as plain text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Identity;
+
+// The price's text in standard base64; decoding takes either alphabet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Base64;
+
+// The encryption-key/integrity-key scheme: the price in micros, XORed with
+// HMAC-SHA1(encryption key, iv), followed by the first 4 bytes of
+// HMAC-SHA1(integrity key, price || iv) and sent as
+// websafe base64(iv || encrypted price || signature) without padding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HmacSha1 {
+    encryption_key: Vec<u8>,
+    integrity_key: Vec<u8>,
+}
+
+const IV_LEN: usize = 16;
+const PRICE_LEN: usize = 8;
+const SIGNATURE_LEN: usize = 4;
+
+impl PriceCodec for Identity {
+    fn encode(&self, price: Price) -> String {
+        price.to_string()
+    }
+
+    fn decode(&self, encoded: &str) -> Result<Price, Error> {
+        parse_price(encoded)
+    }
+}
+
+impl PriceCodec for Base64 {
+    fn encode(&self, price: Price) -> String {
+        base64::encode(price.to_string().as_bytes(), base64::STANDARD)
+    }
+
+    fn decode(&self, encoded: &str) -> Result<Price, Error> {
+        let bytes = base64::decode(encoded).ok_or(Error::Base64)?;
+        parse_price(&String::from_utf8_lossy(&bytes))
+    }
+}
+
+impl HmacSha1 {
+    pub fn new(encryption_key: Vec<u8>, integrity_key: Vec<u8>) -> HmacSha1 {
+        HmacSha1 {
+            encryption_key,
+            integrity_key,
+        }
+    }
+
+    // Keys in the base64 form exchanges hand them out in.
+    pub fn from_base64(encryption_key: &str, integrity_key: &str) -> Result<HmacSha1, Error> {
+        Ok(HmacSha1::new(
+            base64::decode(encryption_key).ok_or(Error::Base64)?,
+            base64::decode(integrity_key).ok_or(Error::Base64)?,
+        ))
+    }
+
+    pub fn encrypt(&self, micros: u64, iv: [u8; IV_LEN]) -> String {
+        let price = micros.to_be_bytes();
+        let pad = hmac(&self.encryption_key, &[&iv]).finalize().into_bytes();
+        let signature = hmac(&self.integrity_key, &[&price, &iv]).finalize().into_bytes();
+
+        let mut bytes = Vec::with_capacity(IV_LEN + PRICE_LEN + SIGNATURE_LEN);
+        bytes.extend_from_slice(&iv);
+        bytes.extend(price.iter().zip(&pad).map(|(p, k)| p ^ k));
+        bytes.extend_from_slice(&signature[..SIGNATURE_LEN]);
+        base64::encode(&bytes, base64::URL_SAFE).trim_end_matches('=').to_string()
+    }
+
+    pub fn decrypt(&self, encoded: &str) -> Result<u64, Error> {
+        let bytes = base64::decode(encoded).ok_or(Error::Base64)?;
+        if bytes.len() != IV_LEN + PRICE_LEN + SIGNATURE_LEN {
+            return Err(Error::Length(bytes.len()));
+        }
+        let (iv, rest) = bytes.split_at(IV_LEN);
+        let (encrypted, signature) = rest.split_at(PRICE_LEN);
+
+        let pad = hmac(&self.encryption_key, &[iv]).finalize().into_bytes();
+        let mut price = [0u8; PRICE_LEN];
+        for (i, p) in price.iter_mut().enumerate() {
+            *p = encrypted[i] ^ pad[i];
+        }
+        // Compared in constant time, so the comparison doesn't leak how
+        // much of a forged signature is right.
+        hmac(&self.integrity_key, &[&price, iv])
+            .verify_truncated_left(signature)
+            .map_err(|_| Error::Signature)?;
+        Ok(u64::from_be_bytes(price))
+    }
+}
+
+impl PriceCodec for HmacSha1 {
+    fn encode(&self, price: Price) -> String {
+        // Negative prices aren't representable and clear at zero.
+        self.encrypt(price.micros().max(0) as u64, iv())
+    }
+
+    fn decode(&self, encoded: &str) -> Result<Price, Error> {
+        let micros = self.decrypt(encoded)?;
+        i64::try_from(micros)
+            .map(Price::from_micros)
+            .map_err(|_| Error::InvalidPrice(micros.to_string()))
+    }
+}
+
+// A clearing price is never negative.
+fn parse_price(s: &str) -> Result<Price, Error> {
+    s.trim()
+        .parse()
+        .ok()
+        .filter(|&price| price >= Price::ZERO)
+        .ok_or_else(|| Error::InvalidPrice(s.to_string()))
+}
+
+fn hmac(key: &[u8], parts: &[&[u8]]) -> Hmac<Sha1> {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("HMAC takes keys of any length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac
+}
+
+// A unique initialization vector: the time in nanoseconds followed by a
+// counter.
+fn iv() -> [u8; IV_LEN] {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    let mut iv = [0u8; IV_LEN];
+    iv[..8].copy_from_slice(&nanos.to_be_bytes());
+    iv[8..].copy_from_slice(&COUNTER.fetch_add(1, Ordering::Relaxed).to_be_bytes());
+    iv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCRYPTION_KEY: &str = "skU7Ax_NL5pPAFyKdkfZjZz2-VhIN8bjj1rVFOaJ_5o=";
+    const INTEGRITY_KEY: &str = "arO23ykdNqUQ5LEoQ0FVmPkBd7xB5CO89PDZlSjpFxo=";
+
+    fn codec() -> HmacSha1 {
+        HmacSha1::from_base64(ENCRYPTION_KEY, INTEGRITY_KEY).unwrap()
+    }
+
+    #[test]
+    fn hmac_sha1_round_trip() {
+        let codec = codec();
+        let price = Price::from_micros(1_234_567);
+        let encoded = codec.encode(price);
+
+        assert_eq!(38, encoded.len());
+        assert_eq!(Ok(price), codec.decode(&encoded));
+        assert_ne!(encoded, codec.encode(price));
+    }
+
+    // The published example: the IV is "abc123def456ghi7".
+    #[test]
+    fn hmac_sha1_published_vector() {
+        let codec = codec();
+
+        assert_eq!(Ok(Price::from_micros(100)), codec.decode("YWJjMTIzZGVmNDU2Z2hpN7fhCuPemCce_6msaw"));
+        assert_eq!(Ok(100), codec.decrypt("YWJjMTIzZGVmNDU2Z2hpN7fhCuPemCce_6msaw"));
+        assert_eq!("YWJjMTIzZGVmNDU2Z2hpN7fhCuPemCce_6msaw", codec.encrypt(100, *b"abc123def456ghi7"));
+        assert_eq!(Err(Error::Signature), codec.decode("YWJjMTIzZGVmNDU2Z2hpN7fhCuPemCce_6mtaw"));
+    }
+
+    #[test]
+    fn hmac_sha1_fixed_iv() {
+        let codec = codec();
+        let encoded = codec.encrypt(1_900_000, [7; IV_LEN]);
+
+        assert!(encoded.starts_with("BwcHBwcHBwcHBwcHBwcHB"));
+        assert_eq!(encoded, codec.encrypt(1_900_000, [7; IV_LEN]));
+        assert_eq!(Ok(1_900_000), codec.decrypt(&encoded));
+    }
+
+    #[test]
+    fn hmac_sha1_errors() {
+        let codec = codec();
+        let encoded = codec.encode(Price::from_micros(2_500_000));
+        let mut tampered = encoded.clone().into_bytes();
+        tampered[25] = if tampered[25] == b'A' { b'B' } else { b'A' };
+
+        assert_eq!(Err(Error::Signature), codec.decode(&String::from_utf8(tampered).unwrap()));
+        assert_eq!(Err(Error::Length(3)), codec.decode("AAAA"));
+        assert_eq!(Err(Error::Base64), codec.decode("not base64!"));
+        let other = HmacSha1::new(b"e".to_vec(), b"i".to_vec());
+        assert_eq!(Err(Error::Signature), other.decode(&encoded));
+    }
+
+    #[test]
+    fn base64_and_identity() {
+        let price = Price::from_micros(1_500_000);
+        assert_eq!("MS41", Base64.encode(price));
+        assert_eq!(Ok(price), Base64.decode("MS41"));
+        assert_eq!("1.5", Identity.encode(price));
+        assert_eq!(Ok(price), Identity.decode("1.5"));
+        assert_eq!("0.1", Identity.encode(Price::from_micros(100_000)));
+        assert_eq!(Err(Error::InvalidPrice("x".to_string())), Identity.decode("x"));
+        assert_eq!(Err(Error::InvalidPrice("-1.5".to_string())), Identity.decode("-1.5"));
+        assert_eq!(Err(Error::InvalidPrice("-1.5".to_string())), Base64.decode("LTEuNQ=="));
+    }
+}
@@ -0,0 +1,254 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::borrow::Cow;
+use std::fmt::Write;
+
+use super::PriceCodec;
+use base64;
+use v2_5::{Bid, BidResponse, LossReason, Price, SeatBid};
+
+const PREFIX: &str = "${";
+const B64: &str = ":B64";
+
+// The values of the OpenRTB 2.5 section 4.4 substitution macros for one
+// bid. Macros whose value is missing expand to an empty string, as the spec
+// asks; `${...}` sequences that aren't auction macros are left alone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Macros<'a> {
+    // ${AUCTION_ID}, the bid request id.
+    pub auction_id: &'a str,
+    // ${AUCTION_BID_ID}, BidResponse.bidid.
+    pub bid_id: Option<&'a str>,
+    // ${AUCTION_IMP_ID}
+    pub imp_id: &'a str,
+    // ${AUCTION_SEAT_ID}
+    pub seat_id: Option<&'a str>,
+    // ${AUCTION_AD_ID}, Bid.adid.
+    pub ad_id: Option<&'a str>,
+    // ${AUCTION_PRICE}, the clearing price.
+    pub price: Price,
+    // Replaces `price` once a codec encoded it.
+    pub encoded_price: Option<String>,
+    // ${AUCTION_CURRENCY}, explicit or implied.
+    pub currency: &'a str,
+    // ${AUCTION_MBR}, the market bid ratio: clearing price / bid price.
+    pub mbr: Option<f64>,
+    // ${AUCTION_LOSS}
+    pub loss: Option<LossReason>,
+}
+
+enum Value<'a> {
+    Str(&'a str),
+    Price(Price),
+    Float(f64),
+    Int(u32),
+    Empty,
+}
+
+impl<'a> Macros<'a> {
+    pub fn new(response: &'a BidResponse, seat_bid: &'a SeatBid, bid: &'a Bid, price: Price) -> Macros<'a> {
+        Macros {
+            auction_id: &response.id,
+            bid_id: response.bid_id.as_deref(),
+            imp_id: &bid.imp_id,
+            seat_id: seat_bid.seat.as_deref(),
+            ad_id: bid.adid.as_deref(),
+            price,
+            encoded_price: None,
+            currency: response.effective_currency(),
+            mbr: if bid.price > Price::ZERO { Some(price.to_f64() / bid.price.to_f64()) } else { None },
+            loss: None,
+        }
+    }
+
+    pub fn with_loss(mut self, loss: LossReason) -> Macros<'a> {
+        self.loss = Some(loss);
+        self
+    }
+
+    // Encodes ${AUCTION_PRICE} with `codec`; the encoding is shared by all
+    // expansions, as an exchange sends one price per bid.
+    pub fn with_price_codec(mut self, codec: &dyn PriceCodec) -> Macros<'a> {
+        self.encoded_price = Some(codec.encode(self.price));
+        self
+    }
+
+    // Expands `s` in a single pass; borrows it when there's nothing to
+    // expand.
+    pub fn expand<'s>(&self, s: &'s str) -> Cow<'s, str> {
+        if !s.contains(PREFIX) {
+            return Cow::Borrowed(s);
+        }
+        let mut out = String::with_capacity(s.len() + 16);
+        self.expand_into(s, &mut out);
+        Cow::Owned(out)
+    }
+
+    // Appends the expansion of `s` to `out`.
+    pub fn expand_into(&self, s: &str, out: &mut String) {
+        let mut rest = s;
+        while let Some(start) = rest.find(PREFIX) {
+            out.push_str(&rest[..start]);
+            let macro_ = &rest[start..];
+            let end = match macro_.find('}') {
+                Some(end) => end,
+                None => {
+                    rest = macro_;
+                    break;
+                }
+            };
+            let name = &macro_[PREFIX.len()..end];
+            // Not a macro name, e.g. an unclosed "${" followed by other
+            // text: keep the "${" and look for a macro right after it.
+            if !name.bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'_' || b == b':') {
+                out.push_str(PREFIX);
+                rest = &macro_[PREFIX.len()..];
+                continue;
+            }
+            let (name, b64) = match name.strip_suffix(B64) {
+                Some(name) => (name, true),
+                None => (name, false),
+            };
+            match self.value(name) {
+                Some(value) if b64 => {
+                    let mut plain = String::new();
+                    write_value(&mut plain, &value);
+                    out.push_str(&base64::encode(plain.as_bytes(), base64::URL_SAFE));
+                }
+                Some(value) => write_value(out, &value),
+                None => out.push_str(&macro_[..=end]),
+            }
+            rest = &macro_[end + 1..];
+        }
+        out.push_str(rest);
+    }
+
+    // Expands nurl, burl, lurl and adm of `bid` in place.
+    pub fn apply(&self, bid: &mut Bid) {
+        for field in [&mut bid.nurl, &mut bid.burl, &mut bid.lurl, &mut bid.adm] {
+            if let Some(s) = field.as_mut() {
+                if let Cow::Owned(expanded) = self.expand(s) {
+                    *s = expanded;
+                }
+            }
+        }
+    }
+
+    fn value(&self, name: &str) -> Option<Value<'_>> {
+        let str_or_empty = |s: Option<&'a str>| s.map_or(Value::Empty, Value::Str);
+        Some(match name {
+            "AUCTION_ID" => Value::Str(self.auction_id),
+            "AUCTION_BID_ID" => str_or_empty(self.bid_id),
+            "AUCTION_IMP_ID" => Value::Str(self.imp_id),
+            "AUCTION_SEAT_ID" => str_or_empty(self.seat_id),
+            "AUCTION_AD_ID" => str_or_empty(self.ad_id),
+            "AUCTION_PRICE" => match self.encoded_price {
+                Some(ref encoded) => Value::Str(encoded),
+                None => Value::Price(self.price),
+            },
+            "AUCTION_CURRENCY" => Value::Str(self.currency),
+            "AUCTION_MBR" => self.mbr.map_or(Value::Empty, Value::Float),
+            "AUCTION_LOSS" => self.loss.clone().map_or(Value::Empty, |l| Value::Int(l.into())),
+            _ => return None,
+        })
+    }
+}
+
+fn write_value(out: &mut String, value: &Value) {
+    // Writing to a String can't fail.
+    let _ = match *value {
+        Value::Str(s) => out.write_str(s),
+        Value::Price(p) => write!(out, "{}", p),
+        Value::Float(f) => write!(out, "{}", f),
+        Value::Int(i) => write!(out, "{}", i),
+        Value::Empty => Ok(()),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use auction::price_codec::HmacSha1;
+
+    fn response() -> BidResponse {
+        let mut bid = Bid::new("bid-1".to_string(), "imp-1".to_string(), Price::from_micros(2_000_000));
+        bid.adid = Some("ad-1".to_string());
+        bid.nurl = Some("https://dsp.example/win?p=${AUCTION_PRICE}&c=${AUCTION_CURRENCY}".to_string());
+        bid.burl = Some("https://dsp.example/bill?p=${AUCTION_PRICE:B64}&i=${AUCTION_IMP_ID}".to_string());
+        bid.lurl = Some("https://dsp.example/loss?r=${AUCTION_LOSS}&mbr=${AUCTION_MBR}".to_string());
+        bid.adm = Some("<img src=\"https://dsp.example/i?a=${AUCTION_ID}\">".to_string());
+        let mut seat_bid = SeatBid::new();
+        seat_bid.seat = Some("seat-1".to_string());
+        seat_bid.bid.push(bid);
+        let mut response = BidResponse::new("req-1".to_string());
+        response.seat_bid.push(seat_bid);
+        response
+    }
+
+    #[test]
+    fn expands_every_macro() {
+        let response = response();
+        let seat_bid = &response.seat_bid[0];
+        let macros = Macros::new(&response, seat_bid, &seat_bid.bid[0], Price::from_micros(1_500_000));
+
+        assert_eq!(
+            "req-1||imp-1|seat-1|ad-1|1.5|USD|0.75|",
+            macros.expand(
+                "${AUCTION_ID}|${AUCTION_BID_ID}|${AUCTION_IMP_ID}|${AUCTION_SEAT_ID}|${AUCTION_AD_ID}|\
+                 ${AUCTION_PRICE}|${AUCTION_CURRENCY}|${AUCTION_MBR}|${AUCTION_LOSS}"
+            )
+        );
+        assert_eq!("MS41|cmVxLTE=", macros.expand("${AUCTION_PRICE:B64}|${AUCTION_ID:B64}"));
+    }
+
+    #[test]
+    fn leaves_other_text_alone() {
+        let response = response();
+        let seat_bid = &response.seat_bid[0];
+        let macros = Macros::new(&response, seat_bid, &seat_bid.bid[0], Price::from_micros(1_500_000));
+
+        assert!(match macros.expand("https://dsp.example/win") {
+            Cow::Borrowed(_) => true,
+            Cow::Owned(_) => false,
+        });
+        assert_eq!("${GDPR}&p=1.5&${AUCTION_PRICE", macros.expand("${GDPR}&p=${AUCTION_PRICE}&${AUCTION_PRICE"));
+        assert_eq!("${GDPR&p=1.5", macros.expand("${GDPR&p=${AUCTION_PRICE}"));
+        assert_eq!("${${${AUCTION_ID x} req-1", macros.expand("${${${AUCTION_ID x} ${AUCTION_ID}"));
+        // Substituted values aren't expanded again.
+        assert_eq!("${AUCTION_ID}", Macros { auction_id: "${AUCTION_ID}", ..macros }.expand("${AUCTION_ID}"));
+    }
+
+    #[test]
+    fn encoded_price() {
+        let response = response();
+        let seat_bid = &response.seat_bid[0];
+        let codec = HmacSha1::new(b"encryption".to_vec(), b"integrity".to_vec());
+        let macros = Macros::new(&response, seat_bid, &seat_bid.bid[0], Price::from_micros(1_500_000)).with_price_codec(&codec);
+
+        let url = macros.expand("https://dsp.example/win?p=${AUCTION_PRICE}");
+        let encoded = url.trim_start_matches("https://dsp.example/win?p=");
+        assert_eq!(38, encoded.len());
+        assert_eq!(Ok(Price::from_micros(1_500_000)), codec.decode(encoded));
+    }
+
+    #[test]
+    fn apply_to_bid() {
+        let response = response();
+        let seat_bid = &response.seat_bid[0];
+        let mut bid = seat_bid.bid[0].clone();
+        Macros::new(&response, seat_bid, &seat_bid.bid[0], Price::from_micros(1_500_000))
+            .with_loss(LossReason::LostToHigherBid)
+            .apply(&mut bid);
+
+        assert_eq!(Some("https://dsp.example/win?p=1.5&c=USD"), bid.nurl.as_deref());
+        assert_eq!(Some("https://dsp.example/bill?p=MS41&i=imp-1"), bid.burl.as_deref());
+        assert_eq!(Some("https://dsp.example/loss?r=102&mbr=0.75"), bid.lurl.as_deref());
+        assert_eq!(Some("<img src=\"https://dsp.example/i?a=req-1\">"), bid.adm.as_deref());
+    }
+}
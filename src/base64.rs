@@ -0,0 +1,86 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Base64, RFC 4648.
+
+pub const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+// The "websafe" alphabet, for URLs.
+pub const URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// Encodes with padding.
+pub fn encode(bytes: &[u8], alphabet: &[u8; 64]) -> String {
+    let mut s = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(char::from(alphabet[(n >> (18 - 6 * i) & 0x3f) as usize]));
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+// Decodes either alphabet, with or without padding.
+pub fn decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim_end_matches('=');
+    if s.len() % 4 == 1 {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(s.len() * 3 / 4);
+    let mut acc: u32 = 0;
+    let mut len = 0;
+    for c in s.bytes() {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'-' | b'+' => 62,
+            b'_' | b'/' => 63,
+            _ => return None,
+        };
+        acc = (acc << 6) | u32::from(v);
+        len += 6;
+        if len >= 8 {
+            len -= 8;
+            bytes.push((acc >> len) as u8);
+            acc &= (1 << len) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc_4648_vectors() {
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for &(plain, encoded) in &vectors {
+            assert_eq!(encoded, encode(plain.as_bytes(), STANDARD));
+            assert_eq!(Some(plain.as_bytes().to_vec()), decode(encoded));
+            assert_eq!(Some(plain.as_bytes().to_vec()), decode(encoded.trim_end_matches('=')));
+        }
+        assert_eq!("-_8=", encode(&[0xfb, 0xff], URL_SAFE));
+        assert_eq!("+/8=", encode(&[0xfb, 0xff], STANDARD));
+        assert_eq!(Some(vec![0xfb, 0xff]), decode("-_8"));
+        assert_eq!(None, decode("Zm9vY"));
+        assert_eq!(None, decode("Zm9v!"));
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Compact binary encodings of the OpenRTB types, for service-to-service
+//! traffic where both ends use this crate.
+//!
+//! The serde helpers, enums and `Category` read and write the same values
+//! in any format, including non-self-describing ones such as bincode. The
+//! objects themselves skip absent optional fields and carry `ext` as JSON
+//! values, so encoding whole requests and responses needs a
+//! self-describing format like the two below.
+
+/// CBOR in "packed" form: struct fields are keyed by their declaration
+/// index instead of their name. Maps (e.g. `ext`) keep their string keys.
+///
+/// Both ends must be built from the same version of this crate, since
+/// adding or reordering fields changes the indices. With the
+/// `unknown-fields` feature every struct carries a flattened map, so
+/// field names are written as strings again.
+#[cfg(feature = "cbor")]
+pub mod cbor {
+    use serde::{Deserialize, Serialize};
+    use serde_cbor;
+
+    pub use serde_cbor::Error;
+
+    pub fn to_vec<T>(value: &T) -> Result<Vec<u8>, Error>
+    where
+        T: Serialize,
+    {
+        serde_cbor::ser::to_vec_packed(value)
+    }
+
+    pub fn from_slice<'a, T>(bytes: &'a [u8]) -> Result<T, Error>
+    where
+        T: Deserialize<'a>,
+    {
+        serde_cbor::from_slice(bytes)
+    }
+}
+
+/// MessagePack with structs encoded as maps keyed by field name.
+#[cfg(feature = "msgpack")]
+pub mod msgpack {
+    use rmp_serde;
+    use serde::{Deserialize, Serialize};
+
+    pub use rmp_serde::decode::Error as DecodeError;
+    pub use rmp_serde::encode::Error as EncodeError;
+
+    pub fn to_vec<T>(value: &T) -> Result<Vec<u8>, EncodeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        rmp_serde::to_vec_named(value)
+    }
+
+    pub fn from_slice<'a, T>(bytes: &'a [u8]) -> Result<T, DecodeError>
+    where
+        T: Deserialize<'a>,
+    {
+        rmp_serde::from_slice(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+
+    use current::*;
+
+    fn request() -> BidRequest {
+        serde_json::from_str(
+            r#"{
+                "id": "80ce30c53c16e6ede735f123ef6e32361bfc7b22",
+                "at": 2,
+                "test": 1,
+                "tmax": 120,
+                "cur": ["USD", "EUR"],
+                "bcat": ["IAB25", "IAB26-3", "XYZ1"],
+                "badv": ["ford.com"],
+                "imp": [{
+                    "id": "1",
+                    "banner": {"w": 300, "h": 250, "btype": [1, 4], "format": [{"w": 300, "h": 250}]},
+                    "instl": 1,
+                    "secure": 0,
+                    "bidfloor": 0.1,
+                    "bidfloorcur": "USD",
+                    "ext": {"bidder": {"placementId": 13144370}}
+                }, {
+                    "id": "2",
+                    "video": {"mimes": ["video/mp4"], "protocols": [2, 3], "boxingallowed": 1},
+                    "native": {"request": "{\"ver\":\"1.2\"}", "ver": "1.2"}
+                }],
+                "app": {"id": "agltb3B1Yi1pbmNyDAsSA0FwcBiJkfIUDA", "bundle": "com.example", "cat": ["IAB1"]},
+                "device": {"ua": "Mozilla/5.0", "dnt": 0, "js": 1, "ip": "192.168.1.8", "pxratio": 2.0},
+                "user": {"id": "55816b39711f9b5acf3b90e313ed29e51665623f", "ext": {"consent": "BOEFEAyOEFEAyAHABDENAI4AAAB9vABAASA"}},
+                "regs": {"coppa": 1},
+                "source": {"fd": 1, "tid": "abc"},
+                "ext": {"prebid": {"debug": true, "targeting": {"pricegranularity": "med"}}}
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn response() -> BidResponse {
+        serde_json::from_str(
+            r#"{
+                "id": "80ce30c53c16e6ede735f123ef6e32361bfc7b22",
+                "cur": "USD",
+                "seatbid": [{
+                    "seat": "512",
+                    "group": 1,
+                    "bid": [{
+                        "id": 1,
+                        "impid": 1,
+                        "price": 9.43,
+                        "nurl": "http://adserver.com/winnotice?impid=102",
+                        "adomain": ["advertiserdomain.com"],
+                        "cat": ["IAB2"],
+                        "w": 300,
+                        "h": 250,
+                        "ext": {"prebid": {"type": "banner"}}
+                    }]
+                }]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[cfg(feature = "cbor")]
+    mod cbor {
+        use super::super::cbor;
+        use super::*;
+
+        #[test]
+        fn round_trip_request() {
+            let req = request();
+            let bytes = cbor::to_vec(&req).unwrap();
+            let decoded: BidRequest = cbor::from_slice(&bytes).unwrap();
+
+            assert_eq!(req, decoded)
+        }
+
+        #[test]
+        fn round_trip_response() {
+            let res = response();
+            let bytes = cbor::to_vec(&res).unwrap();
+            let decoded: BidResponse = cbor::from_slice(&bytes).unwrap();
+
+            assert_eq!(res, decoded)
+        }
+
+        #[test]
+        #[cfg(not(feature = "unknown-fields"))]
+        fn integer_field_keys() {
+            use serde_cbor;
+
+            let bytes = cbor::to_vec(&BidResponse::new("1234".to_string())).unwrap();
+            let value: serde_cbor::Value = serde_cbor::from_slice(&bytes).unwrap();
+
+            match value {
+                serde_cbor::Value::Map(m) => {
+                    let keys: Vec<_> = m.keys().cloned().collect();
+                    assert_eq!(vec![serde_cbor::Value::Integer(0)], keys)
+                }
+                v => panic!("expected a map, got {:?}", v),
+            }
+        }
+
+        #[test]
+        fn smaller_than_json() {
+            let req = request();
+            let json = serde_json::to_vec(&req).unwrap();
+            let bytes = cbor::to_vec(&req).unwrap();
+
+            assert!(bytes.len() < json.len())
+        }
+    }
+
+    #[cfg(feature = "msgpack")]
+    mod msgpack {
+        use super::super::msgpack;
+        use super::*;
+
+        #[test]
+        fn round_trip_request() {
+            let req = request();
+            let bytes = msgpack::to_vec(&req).unwrap();
+            let decoded: BidRequest = msgpack::from_slice(&bytes).unwrap();
+
+            assert_eq!(req, decoded)
+        }
+
+        #[test]
+        fn round_trip_response() {
+            let res = response();
+            let bytes = msgpack::to_vec(&res).unwrap();
+            let decoded: BidResponse = msgpack::from_slice(&bytes).unwrap();
+
+            assert_eq!(res, decoded)
+        }
+
+        #[test]
+        fn bad_bool_is_none() {
+            let mut imp = request().imp.remove(0);
+            imp.ext = None;
+            let mut value = serde_json::to_value(&imp).unwrap();
+            value["instl"] = serde_json::Value::from(7);
+            let bytes = msgpack::to_vec(&value).unwrap();
+            let from_msgpack: Imp = msgpack::from_slice(&bytes).unwrap();
+            let from_json: Imp = serde_json::from_value(value).unwrap();
+
+            assert_eq!(None, from_msgpack.interstitial);
+            assert_eq!(from_json, from_msgpack);
+        }
+    }
+}
@@ -0,0 +1,202 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Currency conversion for floors and bid prices. OpenRTB prices are in
+//! ISO-4217 currencies, USD unless stated otherwise.
+
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use parse;
+use v2_5::Price;
+
+// The currency implied wherever the spec lets it be left out.
+pub const DEFAULT: &str = "USD";
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Parse(parse::Error),
+    // The converter has no rate between the two currencies.
+    UnknownRate { from: String, to: String },
+    // The rate isn't finite, or the converted amount is out of range.
+    InvalidRate { from: String, to: String, rate: f64 },
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref e) => write!(formatter, "reading rates: {}", e),
+            Error::Parse(ref e) => write!(formatter, "parsing rates: {}", e),
+            Error::UnknownRate { ref from, ref to } => write!(formatter, "no rate from {} to {}", from, to),
+            Error::InvalidRate { ref from, ref to, rate } => {
+                write!(formatter, "rate {} from {} to {} can't convert the amount", rate, from, to)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref e) => Some(e),
+            Error::Parse(ref e) => Some(e),
+            Error::UnknownRate { .. } | Error::InvalidRate { .. } => None,
+        }
+    }
+}
+
+pub trait CurrencyConverter {
+    // What one unit of `from` is worth in `to`, if known.
+    fn rate(&self, from: &str, to: &str) -> Option<f64>;
+
+    // `amount` in `from` as `to`, to the nearest micro.
+    fn convert(&self, amount: Price, from: &str, to: &str) -> Result<Price, Error> {
+        if from == to {
+            return Ok(amount);
+        }
+        match self.rate(from, to) {
+            Some(rate) => amount.checked_scale(rate).ok_or_else(|| Error::InvalidRate {
+                from: from.to_string(),
+                to: to.to_string(),
+                rate,
+            }),
+            None => Err(Error::UnknownRate {
+                from: from.to_string(),
+                to: to.to_string(),
+            }),
+        }
+    }
+}
+
+// A bidfloor and bidfloorcur pair converted to `currency`; the floor
+// currency defaults to USD.
+pub fn floor_in(
+    floor: Option<Price>,
+    floor_currency: Option<&str>,
+    currency: &str,
+    converter: &dyn CurrencyConverter,
+) -> Result<Option<Price>, Error> {
+    match floor {
+        Some(floor) => converter.convert(floor, floor_currency.unwrap_or(DEFAULT), currency).map(Some),
+        None => Ok(None),
+    }
+}
+
+// Fixed rates, in the layout of the Prebid Server currency file:
+// `{"dataAsOf": "2018-09-12", "conversions": {"USD": {"EUR": 0.86}}}`.
+// Rates are looked up directly, inverted, or through a common base.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RateTable {
+    #[serde(rename = "dataAsOf", skip_serializing_if = "Option::is_none")]
+    pub data_as_of: Option<String>,
+    #[serde(default)]
+    pub conversions: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+impl RateTable {
+    pub fn parse(bytes: &[u8]) -> Result<RateTable, Error> {
+        parse::from_slice(bytes).map_err(Error::Parse)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<RateTable, Error> {
+        RateTable::parse(&fs::read(path).map_err(Error::Io)?)
+    }
+
+    // Sets what one unit of `from` is worth in `to`.
+    pub fn insert(&mut self, from: &str, to: &str, rate: f64) {
+        self.conversions
+            .entry(from.to_string())
+            .or_default()
+            .insert(to.to_string(), rate);
+    }
+
+    fn direct(&self, from: &str, to: &str) -> Option<f64> {
+        if let Some(&rate) = self.conversions.get(from).and_then(|r| r.get(to)) {
+            return Some(rate);
+        }
+        self.conversions
+            .get(to)
+            .and_then(|r| r.get(from))
+            .filter(|&&rate| rate != 0.0)
+            .map(|rate| 1.0 / rate)
+    }
+}
+
+impl CurrencyConverter for RateTable {
+    fn rate(&self, from: &str, to: &str) -> Option<f64> {
+        if from == to {
+            return Some(1.0);
+        }
+        self.direct(from, to).or_else(|| {
+            self.conversions.keys().find_map(|base| {
+                let from_base = self.direct(from, base)?;
+                let base_to = self.direct(base, to)?;
+                Some(from_base * base_to)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATES: &str = r#"{
+        "dataAsOf": "2018-09-12",
+        "conversions": {
+            "USD": {"EUR": 0.8, "GBP": 0.5},
+            "JPY": {"USD": 0.01}
+        }
+    }"#;
+
+    #[test]
+    fn lookups() {
+        let table = RateTable::parse(RATES.as_bytes()).unwrap();
+
+        assert_eq!(Some(1.0), table.rate("EUR", "EUR"));
+        assert_eq!(Some(0.8), table.rate("USD", "EUR"));
+        assert_eq!(Some(1.25), table.rate("EUR", "USD"));
+        assert_eq!(Some(0.625), table.rate("EUR", "GBP"));
+        assert_eq!(Some(0.008), table.rate("JPY", "EUR"));
+        assert_eq!(None, table.rate("USD", "CHF"));
+    }
+
+    #[test]
+    fn convert() {
+        let mut table = RateTable::default();
+        table.insert("USD", "EUR", 0.5);
+
+        let price = |s: &str| s.parse::<Price>().unwrap();
+        assert_eq!(price("3"), table.convert(price("3"), "CHF", "CHF").unwrap());
+        assert_eq!(price("4"), table.convert(price("2"), "EUR", "USD").unwrap());
+        assert_eq!(price("0.333333"), table.convert(price("0.666666"), "USD", "EUR").unwrap());
+        match table.convert(price("1"), "USD", "CHF") {
+            Err(Error::UnknownRate { from, to }) => assert_eq!(("USD", "CHF"), (from.as_str(), to.as_str())),
+            other => panic!("{:?}", other),
+        }
+
+        for &rate in &[f64::INFINITY, f64::NAN, 1e300] {
+            table.insert("USD", "GBP", rate);
+            assert!(matches!(table.convert(price("1"), "USD", "GBP"), Err(Error::InvalidRate { .. })));
+        }
+    }
+
+    #[test]
+    fn load_errors() {
+        assert!(matches!(RateTable::load("/nonexistent/rates.json"), Err(Error::Io(_))));
+        match RateTable::parse(br#"{"conversions": {"USD": {"EUR": "x"}}}"#) {
+            Err(Error::Parse(e)) => assert_eq!("/conversions/USD/EUR", e.pointer),
+            other => panic!("{:?}", other),
+        }
+    }
+}
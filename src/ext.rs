@@ -0,0 +1,430 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Typed access to the `ext` objects.
+//!
+//! An extension is any serde type implementing `Extension`. It is read from
+//! and written to the top-level keys of an object's `ext` map, so several
+//! extensions owning different keys (e.g. `prebid` and `schain`) can live
+//! side by side in the same map:
+//!
+//! ```
+//! # #[macro_use] extern crate serde_derive;
+//! # extern crate openrtb;
+//! use openrtb::current::Imp;
+//! use openrtb::ext::{Extensible, Extension};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Bidder {
+//!     placement: u64,
+//! }
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct ImpExt {
+//!     bidder: Bidder,
+//! }
+//!
+//! impl Extension for ImpExt {}
+//!
+//! # fn main() {
+//! let mut imp: Imp = openrtb::parse::from_str(r#"{"id": "1"}"#).unwrap();
+//! imp.set_ext(&ImpExt { bidder: Bidder { placement: 13144370 } }).unwrap();
+//!
+//! let ext: ImpExt = imp.ext_as().unwrap().unwrap();
+//! assert_eq!(13144370, ext.bidder.placement);
+//! # }
+//! ```
+//!
+//! `BidRequest` is also generic over its ext type, so a known schema can be
+//! decoded and validated once with `BidRequest::with_typed_ext`. The result
+//! holds a `Typed` ext, which keeps the keys the schema doesn't model and
+//! writes them back. Deserializing a `BidRequest<E>` validates its ext.
+
+use std::error;
+use std::fmt::{self, Display};
+use std::ops::{Deref, DerefMut};
+
+use serde::de::{self, DeserializeOwned};
+use serde::ser;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_ignored;
+use serde_json::{self, Value};
+
+use native::v1_2 as native;
+use serde_utils::Ext;
+use v2_5::*;
+
+pub trait Extension: Serialize + DeserializeOwned {
+    // Checks constraints that the type system can't express. Called
+    // whenever the extension is decoded through this module.
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl Extension for Ext {}
+
+#[derive(Debug)]
+pub enum Error {
+    // The ext map doesn't match the extension's shape.
+    Json(serde_json::Error),
+    // The extension decoded but failed `Extension::validate`.
+    Invalid(String),
+    // The extension didn't serialize to a JSON object.
+    NotAnObject,
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Json(ref e) => write!(formatter, "malformed ext: {}", e),
+            Error::Invalid(ref s) => write!(formatter, "invalid ext: {}", s),
+            Error::NotAnObject => formatter.write_str("ext must serialize to a JSON object"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Json(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Error {
+        Error::Json(e)
+    }
+}
+
+// Decodes and validates an extension from an ext map.
+pub fn decode<T>(ext: &Ext) -> Result<T, Error>
+where
+    T: Extension,
+{
+    let t = T::deserialize(ext)?;
+    t.validate().map_err(Error::Invalid)?;
+    Ok(t)
+}
+
+// Encodes an extension into a fresh ext map.
+pub fn encode<T>(t: &T) -> Result<Ext, Error>
+where
+    T: Extension,
+{
+    match serde_json::to_value(t)? {
+        Value::Object(map) => Ok(map),
+        _ => Err(Error::NotAnObject),
+    }
+}
+
+// Deserializes an optional ext and validates it, for `deserialize_with`.
+pub fn deserialize_validated<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Extension,
+{
+    let t = Option::<T>::deserialize(deserializer)?;
+    if let Some(ref t) = t {
+        t.validate()
+            .map_err(|s| de::Error::custom(Error::Invalid(s)))?;
+    }
+    Ok(t)
+}
+
+// An extension together with the top-level keys of its ext map that the
+// extension doesn't model. Serializing it writes both, the extension's
+// keys taking precedence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Typed<E> {
+    pub ext: E,
+    pub rest: Ext,
+}
+
+impl<E> Typed<E>
+where
+    E: Extension,
+{
+    pub fn new(ext: E) -> Typed<E> {
+        Typed {
+            ext,
+            rest: Ext::new(),
+        }
+    }
+
+    // Decodes and validates an ext map, keeping the keys `E` ignores.
+    pub fn from_map(map: Ext) -> Result<Typed<E>, Error> {
+        let typed: Typed<E> = Typed::split(map)?;
+        typed.validate().map_err(Error::Invalid)?;
+        Ok(typed)
+    }
+
+    fn split(mut map: Ext) -> Result<Typed<E>, serde_json::Error> {
+        let mut ignored = vec![];
+        let ext = serde_ignored::deserialize(&map, |path| {
+            if let serde_ignored::Path::Map {
+                parent: serde_ignored::Path::Root,
+                key,
+            } = path
+            {
+                ignored.push(key);
+            }
+        })?;
+        let rest = ignored
+            .into_iter()
+            .filter_map(|key| map.remove_entry(&key))
+            .collect();
+        Ok(Typed { ext, rest })
+    }
+}
+
+impl<E> Deref for Typed<E> {
+    type Target = E;
+
+    fn deref(&self) -> &E {
+        &self.ext
+    }
+}
+
+impl<E> DerefMut for Typed<E> {
+    fn deref_mut(&mut self) -> &mut E {
+        &mut self.ext
+    }
+}
+
+impl<E> Extension for Typed<E>
+where
+    E: Extension,
+{
+    fn validate(&self) -> Result<(), String> {
+        self.ext.validate()
+    }
+}
+
+impl<E> Serialize for Typed<E>
+where
+    E: Extension,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let map = encode(&self.ext).map_err(ser::Error::custom)?;
+        let rest = self.rest.iter().filter(|&(k, _)| !map.contains_key(k));
+        serializer.collect_map(map.iter().chain(rest))
+    }
+}
+
+impl<'de, E> Deserialize<'de> for Typed<E>
+where
+    E: Extension,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Typed<E>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Typed::split(Ext::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+pub trait Extensible {
+    fn ext_map(&self) -> Option<&Ext>;
+
+    fn ext_map_mut(&mut self) -> &mut Option<Ext>;
+
+    // Decodes the ext map as `T`; `Ok(None)` if there is no ext.
+    fn ext_as<T>(&self) -> Result<Option<T>, Error>
+    where
+        T: Extension,
+    {
+        match self.ext_map() {
+            Some(ext) => decode(ext).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // Writes the top-level keys of `t` into the ext map, replacing keys of
+    // the same name and leaving the others alone.
+    fn set_ext<T>(&mut self, t: &T) -> Result<(), Error>
+    where
+        T: Extension,
+    {
+        t.validate().map_err(Error::Invalid)?;
+        let map = encode(t)?;
+        self.ext_map_mut()
+            .get_or_insert_with(Ext::new)
+            .extend(map);
+        Ok(())
+    }
+}
+
+macro_rules! extensible {
+    ( $( $t:ty ),* ) => {
+        $(
+            impl Extensible for $t {
+                fn ext_map(&self) -> Option<&Ext> {
+                    self.ext.as_ref()
+                }
+
+                fn ext_map_mut(&mut self) -> &mut Option<Ext> {
+                    &mut self.ext
+                }
+            }
+        )*
+    }
+}
+
+extensible!(
+    App,
+    Banner,
+    Bid,
+    BidRequest,
+    BidResponse,
+    Data,
+    Deal,
+    Device,
+    Format,
+    Geo,
+    Imp,
+    Native,
+    PMP,
+    Publisher,
+    Regulations,
+    SeatBid,
+    Site,
+    Source,
+    SupplyChain,
+    SupplyChainNode,
+    User,
+    Video,
+    native::Request,
+    native::Asset,
+    native::Title,
+    native::Image,
+    native::EventTracker,
+    native::Response,
+    native::AssetResponse,
+    native::ImageResponse,
+    native::EventTrackerResponse,
+    native::Video,
+    native::Data,
+    native::DestinationLink,
+    native::TitleResponse,
+    native::DataResponse,
+    native::LinkResponse
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Targeting {
+        pricegranularity: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct PrebidExt {
+        targeting: Targeting,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct RequestExt {
+        prebid: PrebidExt,
+    }
+
+    impl Extension for RequestExt {
+        fn validate(&self) -> Result<(), String> {
+            match self.prebid.targeting.pricegranularity.as_str() {
+                "low" | "med" | "high" | "auto" | "dense" => Ok(()),
+                g => Err(format!("unknown price granularity {}", g)),
+            }
+        }
+    }
+
+    fn request(granularity: &str) -> BidRequest {
+        let serialized = format!(
+            r#"{{
+                "id": "1234",
+                "imp": [],
+                "at": 1,
+                "ext": {{"prebid": {{"targeting": {{"pricegranularity": "{}"}}}}, "schain": {{}}}}
+            }}"#,
+            granularity
+        );
+        serde_json::from_str(&serialized).unwrap()
+    }
+
+    #[test]
+    fn ext_as() {
+        let ext: RequestExt = request("med").ext_as().unwrap().unwrap();
+
+        assert_eq!("med", ext.prebid.targeting.pricegranularity)
+    }
+
+    #[test]
+    fn ext_as_missing() {
+        let b = BidRequest::new("1234".to_string());
+
+        assert!(b.ext_as::<RequestExt>().unwrap().is_none())
+    }
+
+    #[test]
+    fn ext_as_invalid() {
+        match request("fine").ext_as::<RequestExt>() {
+            Err(Error::Invalid(_)) => (),
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn set_ext_keeps_other_keys() {
+        let mut b = request("med");
+        let mut ext: RequestExt = b.ext_as().unwrap().unwrap();
+        ext.prebid.targeting.pricegranularity = "high".to_string();
+        b.set_ext(&ext).unwrap();
+
+        let map = b.ext.unwrap();
+        assert_eq!("high", map["prebid"]["targeting"]["pricegranularity"]);
+        assert!(map.contains_key("schain"))
+    }
+
+    #[test]
+    fn typed_request() {
+        let b = request("med").with_typed_ext::<RequestExt>().unwrap();
+        assert_eq!("med", b.ext.as_ref().unwrap().prebid.targeting.pricegranularity);
+        assert!(b.ext.as_ref().unwrap().rest.contains_key("schain"));
+
+        let untyped = b.into_untyped_ext().unwrap();
+        assert_eq!(request("med"), untyped);
+
+        match request("fine").with_typed_ext::<RequestExt>() {
+            Err(Error::Invalid(_)) => (),
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn typed_request_deserialize() {
+        let serialized = r#"{
+            "id": "1234",
+            "imp": [],
+            "at": 1,
+            "ext": {"prebid": {"targeting": {"pricegranularity": "low"}}}
+        }"#;
+        let b: BidRequest<RequestExt> = serde_json::from_str(serialized).unwrap();
+        assert_eq!("low", b.ext.unwrap().prebid.targeting.pricegranularity);
+
+        let invalid = serialized.replace("low", "fine");
+        assert!(serde_json::from_str::<BidRequest<RequestExt>>(&invalid).is_err());
+        let b: BidRequest<Typed<RequestExt>> = serde_json::from_str(serialized).unwrap();
+        assert!(b.ext.unwrap().rest.is_empty());
+    }
+}
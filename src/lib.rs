@@ -6,19 +6,43 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+extern crate hmac;
 extern crate phf;
 
 #[macro_use]
 extern crate serde_derive;
 
 extern crate serde;
+extern crate serde_ignored;
 extern crate serde_json;
+extern crate serde_path_to_error;
+extern crate sha1;
+
+#[cfg(test)]
+extern crate bincode;
+#[cfg(feature = "msgpack")]
+extern crate rmp_serde;
+#[cfg(feature = "cbor")]
+extern crate serde_cbor;
 
 #[macro_use]
 mod macros;
+mod base64;
 mod serde_utils;
 
+pub mod ads_txt;
+pub mod auction;
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+pub mod binary;
+pub mod currency;
+pub mod ext;
 pub mod native;
+pub mod parse;
+#[cfg(feature = "prebid")]
+pub mod prebid;
+pub mod privacy;
+pub mod sellers_json;
+pub mod strict;
 pub mod v2_5;
 
 pub use v2_5 as current;
@@ -11,6 +11,9 @@ macro_rules! enum_list_module {
       $(
           $variant_name:ident $variant_value:expr
        ),*
+      // Optional variants carrying the values of a range, e.g. the
+      // exchange-specific 500 and up, matched in order.
+      $( ; $range_name:ident $range:pat )*
     ) => {
         use std::fmt::{self, Display};
         use std::convert::TryInto;
@@ -20,6 +23,7 @@ macro_rules! enum_list_module {
         #[derive(Clone, Debug, PartialEq)]
         pub enum $enum_name {
             $($variant_name,)*
+            $($range_name($variant_type),)*
         }
 
         #[derive(Clone, Debug, PartialEq)]
@@ -39,15 +43,19 @@ macro_rules! enum_list_module {
             fn try_into(self) -> Result<$enum_name, Self::Error> {
                 match self {
                     $( $variant_value => Ok($enum_name::$variant_name), )*
+                    $( x @ $range => Ok($enum_name::$range_name(x)), )*
+                    // Unreachable when the range covers every other value.
+                    #[allow(unreachable_patterns)]
                     _ => Err(IntoEnumError{value: self}),
                 }
             }
         }
 
-        impl Into<$variant_type> for $enum_name {
-            fn into(self) -> $variant_type {
-                match self {
+        impl From<$enum_name> for $variant_type {
+            fn from(x: $enum_name) -> $variant_type {
+                match x {
                     $( $enum_name::$variant_name => $variant_value, )*
+                    $( $enum_name::$range_name(x) => x, )*
                 }
             }
         }
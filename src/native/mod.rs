@@ -6,6 +6,73 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{self, Value};
+
 pub mod v1_2;
 
 pub use self::v1_2 as current;
+
+// Native markup travels JSON-encoded in a string field of the OpenRTB
+// object (`Native.request`, `Bid.adm`). Versions 1.0 and 1.1 wrap it in a
+// `{"native": ...}` object; 1.2 doesn't.
+pub fn is_wrapped_version(ver: Option<&str>) -> bool {
+    match ver {
+        Some(v) => v == "1" || v.starts_with("1.0") || v.starts_with("1.1"),
+        None => false,
+    }
+}
+
+// Whether native markup is in the wrapped form: an object whose only key
+// is `native`.
+pub fn is_wrapped(markup: &str) -> bool {
+    match serde_json::from_str::<Value>(markup) {
+        Ok(Value::Object(map)) => map.len() == 1 && map.contains_key("native"),
+        _ => false,
+    }
+}
+
+// Decodes native markup in either form.
+pub fn decode<T>(markup: &str) -> Result<T, serde_json::Error>
+where
+    T: DeserializeOwned,
+{
+    let value = match serde_json::from_str(markup)? {
+        Value::Object(mut map) => match map.remove("native") {
+            Some(inner) if map.is_empty() => inner,
+            Some(inner) => {
+                map.insert("native".to_string(), inner);
+                Value::Object(map)
+            }
+            None => Value::Object(map),
+        },
+        v => v,
+    };
+    serde_json::from_value(value)
+}
+
+#[derive(Serialize)]
+struct Wrapper<'a, T: 'a> {
+    native: &'a T,
+}
+
+// Encodes native markup, wrapped if `ver` calls for it.
+pub fn encode<T>(value: &T, ver: Option<&str>) -> Result<String, serde_json::Error>
+where
+    T: Serialize,
+{
+    encode_as(value, is_wrapped_version(ver))
+}
+
+// Encodes native markup in the given form.
+pub fn encode_as<T>(value: &T, wrapped: bool) -> Result<String, serde_json::Error>
+where
+    T: Serialize,
+{
+    if wrapped {
+        serde_json::to_string(&Wrapper { native: value })
+    } else {
+        serde_json::to_string(value)
+    }
+}
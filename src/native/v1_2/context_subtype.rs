@@ -0,0 +1,78 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+enum_list_module! {
+    ContextSubtype u64:
+        General          10,
+        Article          11,
+        Video            12,
+        Audio            13,
+        Image            14,
+        UserGenerated    15,
+        Social           20,
+        Email            21,
+        ChatIm           22,
+        SellingProduct   30,
+        AppStore         31,
+        ProductReview    32;
+        ExchangeSpecific 500..;
+        // Values below 500 a later version of the spec may define.
+        Reserved         0..=499
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn serialize_good() {
+        let x = ContextSubtype::Article;
+        let expected = "11";
+        let serialized = serde_json::to_string(&x).unwrap();
+
+        assert_eq!(expected, serialized)
+    }
+
+    #[test]
+    fn deserialize_good() {
+        let serialized = "11";
+        let expected = ContextSubtype::Article;
+        let x = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(expected, x)
+    }
+
+    #[test]
+    fn deserialize_exchange_specific() {
+        let serialized = "501";
+        let expected = ContextSubtype::ExchangeSpecific(501);
+        let x = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(expected, x);
+        assert_eq!(serialized, serde_json::to_string(&x).unwrap())
+    }
+
+    #[test]
+    fn deserialize_reserved() {
+        let serialized = "16";
+        let expected = ContextSubtype::Reserved(16);
+        let x = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(expected, x);
+        assert_eq!(serialized, serde_json::to_string(&x).unwrap())
+    }
+
+    #[test]
+    fn deserialize_bad() {
+        let serialized = "\"16\"";
+        let res: Result<ContextSubtype, serde_json::Error> = serde_json::from_str(serialized);
+
+        assert!(res.is_err())
+    }
+}
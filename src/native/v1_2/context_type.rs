@@ -0,0 +1,69 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+enum_list_module! {
+    ContextType u64:
+        Content          1,
+        Social           2,
+        Product          3;
+        ExchangeSpecific 500..;
+        // Values below 500 a later version of the spec may define.
+        Reserved         0..=499
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn serialize_good() {
+        let x = ContextType::Social;
+        let expected = "2";
+        let serialized = serde_json::to_string(&x).unwrap();
+
+        assert_eq!(expected, serialized)
+    }
+
+    #[test]
+    fn deserialize_good() {
+        let serialized = "2";
+        let expected = ContextType::Social;
+        let x = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(expected, x)
+    }
+
+    #[test]
+    fn deserialize_exchange_specific() {
+        let serialized = "501";
+        let expected = ContextType::ExchangeSpecific(501);
+        let x = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(expected, x);
+        assert_eq!(serialized, serde_json::to_string(&x).unwrap())
+    }
+
+    #[test]
+    fn deserialize_reserved() {
+        let serialized = "4";
+        let expected = ContextType::Reserved(4);
+        let x = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(expected, x);
+        assert_eq!(serialized, serde_json::to_string(&x).unwrap())
+    }
+
+    #[test]
+    fn deserialize_bad() {
+        let serialized = "\"4\"";
+        let res: Result<ContextType, serde_json::Error> = serde_json::from_str(serialized);
+
+        assert!(res.is_err())
+    }
+}
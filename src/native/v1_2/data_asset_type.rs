@@ -0,0 +1,78 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+enum_list_module! {
+    DataAssetType u64:
+        Sponsored        1,
+        Desc             2,
+        Rating           3,
+        Likes            4,
+        Downloads        5,
+        Price            6,
+        SalePrice        7,
+        Phone            8,
+        Address          9,
+        Desc2            10,
+        DisplayUrl       11,
+        CtaText          12;
+        ExchangeSpecific 500..;
+        // Values below 500 a later version of the spec may define.
+        Reserved         0..=499
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn serialize_good() {
+        let x = DataAssetType::CtaText;
+        let expected = "12";
+        let serialized = serde_json::to_string(&x).unwrap();
+
+        assert_eq!(expected, serialized)
+    }
+
+    #[test]
+    fn deserialize_good() {
+        let serialized = "12";
+        let expected = DataAssetType::CtaText;
+        let x = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(expected, x)
+    }
+
+    #[test]
+    fn deserialize_exchange_specific() {
+        let serialized = "501";
+        let expected = DataAssetType::ExchangeSpecific(501);
+        let x = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(expected, x);
+        assert_eq!(serialized, serde_json::to_string(&x).unwrap())
+    }
+
+    #[test]
+    fn deserialize_reserved() {
+        let serialized = "13";
+        let expected = DataAssetType::Reserved(13);
+        let x = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(expected, x);
+        assert_eq!(serialized, serde_json::to_string(&x).unwrap())
+    }
+
+    #[test]
+    fn deserialize_bad() {
+        let serialized = "\"13\"";
+        let res: Result<DataAssetType, serde_json::Error> = serde_json::from_str(serialized);
+
+        assert!(res.is_err())
+    }
+}
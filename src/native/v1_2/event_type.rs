@@ -0,0 +1,70 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+enum_list_module! {
+    EventType u64:
+        Impression       1,
+        ViewableMrc50    2,
+        ViewableMrc100   3,
+        ViewableVideo50  4;
+        ExchangeSpecific 500..;
+        // Values below 500 a later version of the spec may define.
+        Reserved         0..=499
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn serialize_good() {
+        let x = EventType::Impression;
+        let expected = "1";
+        let serialized = serde_json::to_string(&x).unwrap();
+
+        assert_eq!(expected, serialized)
+    }
+
+    #[test]
+    fn deserialize_good() {
+        let serialized = "1";
+        let expected = EventType::Impression;
+        let x = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(expected, x)
+    }
+
+    #[test]
+    fn deserialize_exchange_specific() {
+        let serialized = "501";
+        let expected = EventType::ExchangeSpecific(501);
+        let x = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(expected, x);
+        assert_eq!(serialized, serde_json::to_string(&x).unwrap())
+    }
+
+    #[test]
+    fn deserialize_reserved() {
+        let serialized = "5";
+        let expected = EventType::Reserved(5);
+        let x = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(expected, x);
+        assert_eq!(serialized, serde_json::to_string(&x).unwrap())
+    }
+
+    #[test]
+    fn deserialize_bad() {
+        let serialized = "\"5\"";
+        let res: Result<EventType, serde_json::Error> = serde_json::from_str(serialized);
+
+        assert!(res.is_err())
+    }
+}
@@ -8,16 +8,34 @@
 
 use serde_utils;
 
+mod context_subtype;
+mod context_type;
+mod data_asset_type;
+mod event_tracking_method;
+mod event_type;
+mod image_asset_type;
+mod placement_type;
+pub mod render;
+pub mod validate;
+
+pub use self::context_subtype::ContextSubtype;
+pub use self::context_type::ContextType;
+pub use self::data_asset_type::DataAssetType;
+pub use self::event_tracking_method::EventTrackingMethod;
+pub use self::event_type::EventType;
+pub use self::image_asset_type::ImageAssetType;
+pub use self::placement_type::PlacementType;
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Request {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ver: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub context: Option<u64>,
+    pub context: Option<ContextType>,
     #[serde(rename = "contextsubtype", skip_serializing_if = "Option::is_none")]
-    pub context_subtype: Option<u64>,
+    pub context_subtype: Option<ContextSubtype>,
     #[serde(rename = "plcmttype", skip_serializing_if = "Option::is_none")]
-    pub placement_type: Option<u64>,
+    pub placement_type: Option<PlacementType>,
     #[serde(rename = "plcmtcnt", skip_serializing_if = "Option::is_none")]
     pub placement_count: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -34,10 +52,20 @@ pub struct Request {
         skip_serializing_if = "Vec::is_empty"
     )]
     pub event_trackers: Vec<EventTracker>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub privacy: Option<u64>,
+    // Whether the privacy notice may be left out of the ad, the bidder
+    // then rendering it through Response.privacy.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serde_utils::mbool_to_u8",
+        deserialize_with = "serde_utils::u8_to_mbool"
+    )]
+    pub privacy: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -55,6 +83,9 @@ pub struct Asset {
     pub data: Option<Data>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -62,12 +93,15 @@ pub struct Title {
     pub len: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Image {
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub type_: Option<u64>,
+    pub type_: Option<ImageAssetType>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub w: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -80,28 +114,65 @@ pub struct Image {
     pub mimes: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct Video {}
+pub struct Video {
+    #[serde(default)]
+    pub mimes: Vec<String>,
+    // Durations in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minduration: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maxduration: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protocols: Vec<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct Data {}
+pub struct Data {
+    #[serde(rename = "type")]
+    pub type_: DataAssetType,
+    // Maximum length of the value, in characters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub len: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct EventTracker {
-    pub event: u64,
-    pub method: u64,
+    pub event: EventType,
+    // The tracking methods supported for the event.
+    #[serde(default)]
+    pub methods: Vec<EventTrackingMethod>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Response {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub ver: Option<String>,
+    #[serde(default)]
     pub assets: Vec<AssetResponse>,
     #[serde(rename = "assetsurl", skip_serializing_if = "Option::is_none")]
     pub assets_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub dcourl: Option<String>,
     pub link: DestinationLink,
     #[serde(default, rename = "imptrackers", skip_serializing_if = "Vec::is_empty")]
@@ -114,57 +185,136 @@ pub struct Response {
         skip_serializing_if = "Vec::is_empty"
     )]
     pub event_trackers: Vec<EventTrackerResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub privacy: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct DestinationLink {}
+pub struct DestinationLink {
+    // Landing URL of the clickable link. Required by the spec; optional
+    // here so that a response without it can be read and reported by
+    // `validate`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, rename = "clicktrackers", skip_serializing_if = "Vec::is_empty")]
+    pub click_trackers: Vec<String>,
+    // Fallback URL for a deep link in `url`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct AssetResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub required: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<TitleResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub img: Option<ImageResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub video: Option<VideoResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<DataResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub link: Option<LinkResponse>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct TitleResponse {}
+pub struct TitleResponse {
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub len: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ImageResponse {
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub type_: Option<u64>,
+    pub type_: Option<ImageAssetType>,
     pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub w: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub h: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct VideoResponse {}
+pub struct VideoResponse {
+    // VAST XML.
+    #[serde(rename = "vasttag")]
+    pub vast_tag: String,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct DataResponse {}
+pub struct DataResponse {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_: Option<DataAssetType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub len: Option<u64>,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct LinkResponse {}
+pub struct LinkResponse {
+    // Optional for the same reason as `DestinationLink::url`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, rename = "clicktrackers", skip_serializing_if = "Vec::is_empty")]
+    pub click_trackers: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct EventTrackerResponse {
-    pub event: u64,
-    pub method: u64,
+    pub event: EventType,
+    pub method: EventTrackingMethod,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
     #[serde(rename = "customdata", skip_serializing_if = "Option::is_none")]
     pub custom_data: Option<serde_utils::Ext>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
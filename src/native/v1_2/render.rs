@@ -0,0 +1,412 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Renders a native response into HTML for publishers that can't render
+//! native ads themselves.
+//!
+//! Templates are HTML with `{{slot}}` placeholders:
+//!
+//! - `title`, `icon`, `image`, `image_w`, `image_h`
+//! - the data assets by their spec name: `sponsored`, `desc`, `rating`,
+//!   `likes`, `downloads`, `price`, `saleprice`, `phone`, `address`,
+//!   `desc2`, `displayurl`, `ctatext`
+//! - `click_url` and `onclick`, the JavaScript firing the click trackers
+//! - `privacy_url` and `adchoices`, a ready-made AdChoices link
+//! - `trackers`, the impression and event tracker pixels and scripts
+//!
+//! Values are HTML-escaped, and URLs other than http, https and
+//! protocol-relative ones are dropped. Slots without a value render empty,
+//! and `trackers` and `adchoices` are appended when the template leaves
+//! them out.
+
+use std::collections::HashMap;
+
+use super::{DataAssetType, EventTrackingMethod, EventType, ImageAssetType, Request, Response};
+
+pub const DEFAULT_TEMPLATE: &str = "<div class=\"native-ad\">\
+<a href=\"{{click_url}}\" onclick=\"{{onclick}}\" target=\"_blank\">\
+<img class=\"native-icon\" src=\"{{icon}}\" alt=\"\">\
+<img class=\"native-image\" src=\"{{image}}\" alt=\"\">\
+<div class=\"native-title\">{{title}}</div>\
+<div class=\"native-desc\">{{desc}}</div>\
+<div class=\"native-sponsored\">{{sponsored}}</div>\
+<span class=\"native-cta\">{{ctatext}}</span>\
+</a>{{adchoices}}{{trackers}}</div>";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Slot(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    // An unterminated `{{` is kept as text.
+    pub fn new(source: &str) -> Template {
+        let mut parts = vec![];
+        let mut rest = source;
+        while let Some(start) = rest.find("{{") {
+            let end = match rest[start..].find("}}") {
+                Some(end) => start + end,
+                None => break,
+            };
+            if start > 0 {
+                parts.push(Part::Text(rest[..start].to_string()));
+            }
+            parts.push(Part::Slot(rest[start + 2..end].trim().to_string()));
+            rest = &rest[end + 2..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+        Template { parts }
+    }
+
+    // `request` resolves the image and data types of responses that only
+    // give asset ids, as 1.2 allows.
+    pub fn render(&self, response: &Response, request: Option<&Request>) -> String {
+        let slots = slots(response, request);
+        let mut html = String::new();
+        for part in &self.parts {
+            match *part {
+                Part::Text(ref text) => html.push_str(text),
+                Part::Slot(ref name) => {
+                    if let Some(value) = slots.get(name.as_str()) {
+                        html.push_str(value);
+                    }
+                }
+            }
+        }
+        for name in &["adchoices", "trackers"] {
+            if !self.has_slot(name) {
+                html.push_str(&slots[*name]);
+            }
+        }
+        html
+    }
+
+    fn has_slot(&self, name: &str) -> bool {
+        self.parts.iter().any(|p| match *p {
+            Part::Slot(ref slot) => slot == name,
+            Part::Text(_) => false,
+        })
+    }
+}
+
+impl Default for Template {
+    fn default() -> Template {
+        Template::new(DEFAULT_TEMPLATE)
+    }
+}
+
+// Renders `response` with the default template.
+pub fn render(response: &Response, request: Option<&Request>) -> String {
+    Template::default().render(response, request)
+}
+
+// The values of the slots, already escaped.
+fn slots(response: &Response, request: Option<&Request>) -> HashMap<&'static str, String> {
+    let mut slots = HashMap::new();
+    let requested = |id: Option<u64>| {
+        request.and_then(|r| r.assets.iter().find(|a| Some(a.id) == id))
+    };
+
+    let mut images = vec![];
+    for asset in &response.assets {
+        if let Some(ref title) = asset.title {
+            slots.entry("title").or_insert_with(|| escape(&title.text));
+        }
+        if let Some(ref img) = asset.img {
+            let type_ = img
+                .type_
+                .clone()
+                .or_else(|| requested(asset.id).and_then(|a| a.img.as_ref()?.type_.clone()));
+            images.push((type_, img));
+        }
+        if let Some(ref data) = asset.data {
+            let type_ = data
+                .type_
+                .clone()
+                .or_else(|| requested(asset.id).and_then(|a| a.data.as_ref()).map(|d| d.type_.clone()));
+            if let Some(name) = type_.and_then(data_slot) {
+                slots.entry(name).or_insert_with(|| escape(&data.value));
+            }
+        }
+    }
+
+    // An image of unknown type stands in for a missing main image.
+    let main = images
+        .iter()
+        .find(|i| i.0 == Some(ImageAssetType::Main))
+        .or_else(|| images.iter().find(|i| i.0.is_none()));
+    if let Some(&(_, img)) = main {
+        slots.insert("image", escape_url(&img.url));
+        slots.insert("image_w", img.w.map(|w| w.to_string()).unwrap_or_default());
+        slots.insert("image_h", img.h.map(|h| h.to_string()).unwrap_or_default());
+    }
+    if let Some(&(_, img)) = images.iter().find(|i| i.0 == Some(ImageAssetType::Icon)) {
+        slots.insert("icon", escape_url(&img.url));
+    }
+
+    slots.insert("click_url", escape_url(response.link.url.as_deref().unwrap_or_default()));
+    let onclick: String = response
+        .link
+        .click_trackers
+        .iter()
+        .filter_map(|url| safe_url(url))
+        .map(|url| format!("new Image().src='{}';", escape_js(url)))
+        .collect();
+    slots.insert("onclick", escape(&onclick));
+
+    let privacy = response.privacy.as_deref().and_then(safe_url);
+    slots.insert("privacy_url", privacy.map(escape).unwrap_or_default());
+    slots.insert(
+        "adchoices",
+        privacy
+            .map(|p| {
+                format!(
+                    "<a class=\"native-adchoices\" href=\"{}\" target=\"_blank\">AdChoices</a>",
+                    escape(p)
+                )
+            })
+            .unwrap_or_default(),
+    );
+
+    slots.insert("trackers", trackers(response));
+    slots
+}
+
+fn data_slot(type_: DataAssetType) -> Option<&'static str> {
+    Some(match type_ {
+        DataAssetType::Sponsored => "sponsored",
+        DataAssetType::Desc => "desc",
+        DataAssetType::Rating => "rating",
+        DataAssetType::Likes => "likes",
+        DataAssetType::Downloads => "downloads",
+        DataAssetType::Price => "price",
+        DataAssetType::SalePrice => "saleprice",
+        DataAssetType::Phone => "phone",
+        DataAssetType::Address => "address",
+        DataAssetType::Desc2 => "desc2",
+        DataAssetType::DisplayUrl => "displayurl",
+        DataAssetType::CtaText => "ctatext",
+        DataAssetType::ExchangeSpecific(_) | DataAssetType::Reserved(_) => return None,
+    })
+}
+
+// Impression pixels and scripts. Image pixels of the viewability events
+// are left out, since firing them needs a measurement the page doesn't
+// make; their scripts are loaded as they do their own.
+fn trackers(response: &Response) -> String {
+    let mut html = String::new();
+    for url in response.imp_trackers.iter().filter_map(|url| safe_url(url)) {
+        html.push_str(&pixel(url));
+    }
+    for tracker in &response.event_trackers {
+        let url = match tracker.url.as_deref().and_then(safe_url) {
+            Some(url) => url,
+            None => continue,
+        };
+        match tracker.method {
+            EventTrackingMethod::Img if tracker.event == EventType::Impression => html.push_str(&pixel(url)),
+            EventTrackingMethod::Js => {
+                html.push_str(&format!("<script async src=\"{}\"></script>", escape(url)))
+            }
+            _ => (),
+        }
+    }
+    // jstracker is HTML already.
+    if let Some(ref js) = response.js_tracker {
+        html.push_str(js);
+    }
+    html
+}
+
+fn pixel(url: &str) -> String {
+    format!(
+        "<img src=\"{}\" width=\"1\" height=\"1\" style=\"display:none\" alt=\"\">",
+        escape(url)
+    )
+}
+
+// Only http, https and protocol-relative URLs may go in an attribute; a
+// javascript: or data: URL from the bidder would run on the page.
+fn safe_url(url: &str) -> Option<&str> {
+    let url = url.trim_matches(|c: char| c <= ' ');
+    let scheme = url.get(..8).unwrap_or(url).to_ascii_lowercase();
+    if scheme.starts_with("http://") || scheme.starts_with("https://") || scheme.starts_with("//") {
+        Some(url)
+    } else {
+        None
+    }
+}
+
+// A URL for an attribute, empty if it isn't safe.
+fn escape_url(url: &str) -> String {
+    safe_url(url).map(escape).unwrap_or_default()
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// For a single-quoted JavaScript string; the result still needs HTML
+// escaping to go in an attribute.
+fn escape_js(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' | '\'' | '"' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '<' => escaped.push_str("\\x3c"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    const REQUEST: &str = r#"{
+        "assets": [
+            {"id": 1, "title": {"len": 25}},
+            {"id": 2, "img": {"type": 3, "wmin": 1200, "hmin": 627}},
+            {"id": 3, "img": {"type": 1}},
+            {"id": 4, "data": {"type": 2}},
+            {"id": 5, "data": {"type": 12}}
+        ]
+    }"#;
+
+    const RESPONSE: &str = r#"{
+        "assets": [
+            {"id": 1, "title": {"text": "Fish & <Chips>"}},
+            {"id": 2, "img": {"url": "https://example.com/main.jpg", "w": 1200, "h": 627}},
+            {"id": 3, "img": {"url": "https://example.com/icon.png"}},
+            {"id": 4, "data": {"value": "Crispy"}},
+            {"id": 5, "data": {"value": "Order"}}
+        ],
+        "link": {"url": "https://example.com/?a=1&b=2", "clicktrackers": ["https://t.example/c?x='1'"]},
+        "imptrackers": ["https://t.example/i"],
+        "eventtrackers": [
+            {"event": 1, "method": 1, "url": "https://t.example/e"},
+            {"event": 2, "method": 1, "url": "https://t.example/v"},
+            {"event": 2, "method": 2, "url": "https://t.example/v.js"}
+        ],
+        "privacy": "https://example.com/privacy"
+    }"#;
+
+    fn parse() -> (Request, Response) {
+        (
+            serde_json::from_str(REQUEST).unwrap(),
+            serde_json::from_str(RESPONSE).unwrap(),
+        )
+    }
+
+    #[test]
+    fn caller_template() {
+        let (request, response) = parse();
+        let template = Template::new(
+            "<h1>{{ title }}</h1><img src=\"{{image}}\" width=\"{{image_w}}\"><i>{{icon}}</i>\
+             <p>{{desc}}</p><a href=\"{{click_url}}\" onclick=\"{{onclick}}\">{{ctatext}}</a>{{unknown}}",
+        );
+
+        assert_eq!(
+            "<h1>Fish &amp; &lt;Chips&gt;</h1>\
+             <img src=\"https://example.com/main.jpg\" width=\"1200\">\
+             <i>https://example.com/icon.png</i>\
+             <p>Crispy</p>\
+             <a href=\"https://example.com/?a=1&amp;b=2\" \
+             onclick=\"new Image().src=&#39;https://t.example/c?x=\\&#39;1\\&#39;&#39;;\">Order</a>\
+             <a class=\"native-adchoices\" href=\"https://example.com/privacy\" target=\"_blank\">AdChoices</a>\
+             <img src=\"https://t.example/i\" width=\"1\" height=\"1\" style=\"display:none\" alt=\"\">\
+             <img src=\"https://t.example/e\" width=\"1\" height=\"1\" style=\"display:none\" alt=\"\">\
+             <script async src=\"https://t.example/v.js\"></script>",
+            template.render(&response, Some(&request))
+        );
+    }
+
+    #[test]
+    fn types_need_the_request() {
+        let (_, response) = parse();
+        let template = Template::new("{{image}}|{{icon}}|{{desc}}|{{adchoices}}|{{trackers}}");
+
+        // Without the request both images are of unknown type, the first
+        // standing in for the main image.
+        assert_eq!(
+            "https://example.com/main.jpg|||\
+             <a class=\"native-adchoices\" href=\"https://example.com/privacy\" target=\"_blank\">AdChoices</a>|\
+             <img src=\"https://t.example/i\" width=\"1\" height=\"1\" style=\"display:none\" alt=\"\">\
+             <img src=\"https://t.example/e\" width=\"1\" height=\"1\" style=\"display:none\" alt=\"\">\
+             <script async src=\"https://t.example/v.js\"></script>",
+            template.render(&response, None)
+        );
+    }
+
+    #[test]
+    fn default_template() {
+        let (request, response) = parse();
+        let html = render(&response, Some(&request));
+
+        assert!(html.starts_with("<div class=\"native-ad\"><a href=\"https://example.com/?a=1&amp;b=2\""));
+        assert!(html.contains("<div class=\"native-title\">Fish &amp; &lt;Chips&gt;</div>"));
+        assert!(html.contains("<span class=\"native-cta\">Order</span>"));
+        assert_eq!(1, html.matches("AdChoices").count());
+        assert!(html.ends_with("</script></div>"));
+    }
+
+    #[test]
+    fn unsafe_urls_are_dropped() {
+        let (request, mut response) = parse();
+        let script = "javascript:alert(document.cookie)";
+        response.link.url = Some(script.to_string());
+        response.link.click_trackers = vec![script.to_string()];
+        response.assets[1].img.as_mut().unwrap().url = "data:text/html,<script>alert(1)</script>".to_string();
+        response.assets[2].img.as_mut().unwrap().url = "//example.com/icon.png".to_string();
+        response.privacy = Some(" JavaScript:alert(1)".to_string());
+        response.imp_trackers = vec!["vbscript:msgbox(1)".to_string()];
+        for tracker in &mut response.event_trackers {
+            tracker.url = Some(script.to_string());
+        }
+        let template =
+            Template::new("{{click_url}}|{{onclick}}|{{image}}|{{icon}}|{{privacy_url}}|{{adchoices}}|{{trackers}}");
+
+        assert_eq!("|||//example.com/icon.png|||", template.render(&response, Some(&request)));
+    }
+
+    #[test]
+    fn unterminated_slot() {
+        let (_, mut response) = parse();
+        response.privacy = None;
+        response.imp_trackers.clear();
+        response.event_trackers.clear();
+
+        assert_eq!("a {{title", Template::new("a {{title").render(&response, None));
+    }
+}
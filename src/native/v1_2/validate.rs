@@ -0,0 +1,324 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Checks a native response against the request it answers, with the rules
+//! exchanges apply before accepting a native bid.
+
+use std::error;
+use std::fmt::{self, Display};
+
+use super::{Asset, AssetResponse, EventTrackingMethod, EventType, Image, ImageResponse, Request, Response};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    Exactly(u64),
+    AtLeast(u64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    // A required asset of the request is missing.
+    MissingRequiredAsset(u64),
+    // The response carries an asset id the request didn't offer.
+    UnknownAsset(u64),
+    // The asset at this index of the response has no id.
+    MissingAssetId(usize),
+    // The asset isn't of the kind (title, image, video, data) requested.
+    AssetTypeMismatch(u64),
+    TitleTooLong { id: u64, len: usize, max: u64 },
+    ImageWidth { id: u64, w: u64, expected: Dimension },
+    ImageHeight { id: u64, h: u64, expected: Dimension },
+    DataTooLong { id: u64, len: usize, max: u64 },
+    // The request didn't ask for this event/method pair.
+    EventTrackerNotRequested {
+        event: EventType,
+        method: EventTrackingMethod,
+    },
+    // The response's link, or the link of this asset, has no URL.
+    MissingLinkUrl(Option<u64>),
+}
+
+impl Display for Dimension {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Dimension::Exactly(n) => write!(formatter, "{}", n),
+            Dimension::AtLeast(n) => write!(formatter, "at least {}", n),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::MissingRequiredAsset(id) => write!(formatter, "required asset {} is missing", id),
+            Error::UnknownAsset(id) => write!(formatter, "asset {} was not requested", id),
+            Error::MissingAssetId(i) => write!(formatter, "asset at index {} has no id", i),
+            Error::AssetTypeMismatch(id) => write!(formatter, "asset {} is not of the requested type", id),
+            Error::TitleTooLong { id, len, max } => {
+                write!(formatter, "title of asset {} has {} characters, max {}", id, len, max)
+            }
+            Error::ImageWidth { id, w, expected } => {
+                write!(formatter, "image of asset {} is {} wide, expected {}", id, w, expected)
+            }
+            Error::ImageHeight { id, h, expected } => {
+                write!(formatter, "image of asset {} is {} high, expected {}", id, h, expected)
+            }
+            Error::DataTooLong { id, len, max } => {
+                write!(formatter, "data of asset {} has {} characters, max {}", id, len, max)
+            }
+            Error::EventTrackerNotRequested {
+                ref event,
+                ref method,
+            } => write!(
+                formatter,
+                "event tracker for event {} with method {} was not requested",
+                u64::from(event.clone()),
+                u64::from(method.clone())
+            ),
+            Error::MissingLinkUrl(Some(id)) => write!(formatter, "link of asset {} has no url", id),
+            Error::MissingLinkUrl(None) => formatter.write_str("link has no url"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+// Every rule `response` breaks, in the order: link, assets, event trackers.
+pub fn validate(request: &Request, response: &Response) -> Result<(), Vec<Error>> {
+    let mut errors = vec![];
+
+    if is_missing(&response.link.url) {
+        errors.push(Error::MissingLinkUrl(None));
+    }
+
+    // The assets may come from assetsurl or dcourl instead, in which case
+    // there is nothing to compare.
+    let remote = response.assets.is_empty() && (response.assets_url.is_some() || response.dcourl.is_some());
+    if !remote {
+        for asset in request.assets.iter().filter(|a| a.required == Some(1)) {
+            if !response.assets.iter().any(|r| r.id == Some(asset.id)) {
+                errors.push(Error::MissingRequiredAsset(asset.id));
+            }
+        }
+    }
+
+    for (i, response_asset) in response.assets.iter().enumerate() {
+        let id = match response_asset.id {
+            Some(id) => id,
+            None => {
+                errors.push(Error::MissingAssetId(i));
+                continue;
+            }
+        };
+        match request.assets.iter().find(|a| a.id == id) {
+            Some(asset) => check_asset(id, asset, response_asset, &mut errors),
+            None => errors.push(Error::UnknownAsset(id)),
+        }
+    }
+
+    for tracker in &response.event_trackers {
+        let requested = request
+            .event_trackers
+            .iter()
+            .any(|t| t.event == tracker.event && t.methods.contains(&tracker.method));
+        if !requested {
+            errors.push(Error::EventTrackerNotRequested {
+                event: tracker.event.clone(),
+                method: tracker.method.clone(),
+            });
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn check_asset(id: u64, asset: &Asset, response: &AssetResponse, errors: &mut Vec<Error>) {
+    let matches = (asset.title.is_some() && response.title.is_some())
+        || (asset.img.is_some() && response.img.is_some())
+        || (asset.video.is_some() && response.video.is_some())
+        || (asset.data.is_some() && response.data.is_some());
+    if !matches {
+        errors.push(Error::AssetTypeMismatch(id));
+        return;
+    }
+
+    if let (Some(title), Some(text)) = (asset.title.as_ref(), response.title.as_ref()) {
+        let len = text.text.chars().count();
+        if len as u64 > title.len {
+            errors.push(Error::TitleTooLong { id, len, max: title.len });
+        }
+    }
+    if let (Some(img), Some(image)) = (asset.img.as_ref(), response.img.as_ref()) {
+        check_image(id, img, image, errors);
+    }
+    if let (Some(data), Some(value)) = (asset.data.as_ref(), response.data.as_ref()) {
+        let len = value.value.chars().count();
+        match data.len {
+            Some(max) if len as u64 > max => errors.push(Error::DataTooLong { id, len, max }),
+            _ => (),
+        }
+    }
+    if let Some(ref link) = response.link {
+        if is_missing(&link.url) {
+            errors.push(Error::MissingLinkUrl(Some(id)));
+        }
+    }
+}
+
+// The minimum wins over the exact size when both are given. Sizes the
+// response leaves out can't be checked.
+fn check_image(id: u64, img: &Image, image: &ImageResponse, errors: &mut Vec<Error>) {
+    if let (Some(w), Some(expected)) = (image.w, dimension(img.w, img.wmin)) {
+        if !fits(w, expected) {
+            errors.push(Error::ImageWidth { id, w, expected });
+        }
+    }
+    if let (Some(h), Some(expected)) = (image.h, dimension(img.h, img.hmin)) {
+        if !fits(h, expected) {
+            errors.push(Error::ImageHeight { id, h, expected });
+        }
+    }
+}
+
+fn dimension(exact: Option<u64>, min: Option<u64>) -> Option<Dimension> {
+    match (exact, min) {
+        (_, Some(min)) => Some(Dimension::AtLeast(min)),
+        (Some(exact), None) => Some(Dimension::Exactly(exact)),
+        (None, None) => None,
+    }
+}
+
+fn fits(size: u64, expected: Dimension) -> bool {
+    match expected {
+        Dimension::Exactly(n) => size == n,
+        Dimension::AtLeast(n) => size >= n,
+    }
+}
+
+fn is_missing(url: &Option<String>) -> bool {
+    url.as_ref().is_none_or(|url| url.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    const REQUEST: &str = r#"{
+        "ver": "1.2",
+        "assets": [
+            {"id": 1, "required": 1, "title": {"len": 25}},
+            {"id": 2, "required": 1, "img": {"type": 3, "wmin": 1200, "hmin": 627}},
+            {"id": 3, "img": {"type": 1, "w": 50, "h": 50}},
+            {"id": 4, "data": {"type": 2, "len": 10}}
+        ],
+        "eventtrackers": [{"event": 1, "methods": [1, 2]}]
+    }"#;
+
+    fn check(response: &str) -> Result<(), Vec<Error>> {
+        let request: Request = serde_json::from_str(REQUEST).unwrap();
+        let response: Response = serde_json::from_str(response).unwrap();
+        validate(&request, &response)
+    }
+
+    #[test]
+    fn valid_response() {
+        let response = r#"{
+            "assets": [
+                {"id": 1, "title": {"text": "Learn more"}},
+                {"id": 2, "img": {"url": "https://example.com/main.jpg", "w": 1200, "h": 628}},
+                {"id": 4, "data": {"value": "Buy now"}}
+            ],
+            "link": {"url": "https://example.com/landing"},
+            "eventtrackers": [{"event": 1, "method": 2, "url": "https://example.com/imp.js"}]
+        }"#;
+
+        assert_eq!(Ok(()), check(response));
+    }
+
+    #[test]
+    fn every_failure_is_reported() {
+        let response = r#"{
+            "assets": [
+                {"id": 1, "title": {"text": "Learn about this awesome thing today"}},
+                {"id": 3, "img": {"url": "https://example.com/icon.png", "w": 50, "h": 40}},
+                {"id": 4, "data": {"value": "Buy it right now"}, "link": {"url": ""}},
+                {"id": 9, "title": {"text": "Extra"}},
+                {"title": {"text": "No id"}}
+            ],
+            "link": {"url": ""},
+            "eventtrackers": [{"event": 2, "method": 1}]
+        }"#;
+
+        assert_eq!(
+            Err(vec![
+                Error::MissingLinkUrl(None),
+                Error::MissingRequiredAsset(2),
+                Error::TitleTooLong { id: 1, len: 36, max: 25 },
+                Error::ImageHeight {
+                    id: 3,
+                    h: 40,
+                    expected: Dimension::Exactly(50)
+                },
+                Error::DataTooLong { id: 4, len: 16, max: 10 },
+                Error::MissingLinkUrl(Some(4)),
+                Error::UnknownAsset(9),
+                Error::MissingAssetId(4),
+                Error::EventTrackerNotRequested {
+                    event: EventType::ViewableMrc50,
+                    method: EventTrackingMethod::Img
+                },
+            ]),
+            check(response)
+        );
+    }
+
+    #[test]
+    fn asset_type_mismatch_and_min_size() {
+        let response = r#"{
+            "assets": [
+                {"id": 1, "data": {"value": "Not a title"}},
+                {"id": 2, "img": {"url": "https://example.com/main.jpg", "w": 600, "h": 627}}
+            ],
+            "link": {"url": "https://example.com/landing"}
+        }"#;
+
+        assert_eq!(
+            Err(vec![
+                Error::AssetTypeMismatch(1),
+                Error::ImageWidth {
+                    id: 2,
+                    w: 600,
+                    expected: Dimension::AtLeast(1200)
+                },
+            ]),
+            check(response)
+        );
+    }
+
+    #[test]
+    fn link_without_url() {
+        let response = r#"{"assets": [
+            {"id": 1, "title": {"text": "Learn more"}},
+            {"id": 2, "img": {"url": "https://example.com/main.jpg", "w": 1200, "h": 628}, "link": {}}
+        ], "link": {"clicktrackers": ["https://example.com/click"]}}"#;
+
+        assert_eq!(Err(vec![Error::MissingLinkUrl(None), Error::MissingLinkUrl(Some(2))]), check(response));
+    }
+
+    #[test]
+    fn remote_assets() {
+        let response = r#"{"assetsurl": "https://example.com/assets.json", "link": {"url": "https://example.com"}}"#;
+
+        assert_eq!(Ok(()), check(response));
+    }
+}
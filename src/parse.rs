@@ -0,0 +1,165 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! JSON parsing with diagnostics: on failure the error carries the JSON
+//! pointer of the offending value (e.g. `/imp/3/video/protocols/1`) and the
+//! value itself, in addition to serde_json's message.
+
+use std::error;
+use std::fmt::{self, Display};
+
+use serde::de::DeserializeOwned;
+use serde_json::{self, Value};
+use serde_path_to_error;
+
+#[derive(Debug)]
+pub struct Error {
+    // JSON pointer to the value that failed; empty for the document root
+    // or when the input isn't valid JSON.
+    pub pointer: String,
+
+    // The offending value, if the input is valid JSON.
+    pub value: Option<Value>,
+
+    pub error: serde_json::Error,
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if !self.pointer.is_empty() {
+            write!(formatter, "{}: ", self.pointer)?;
+        }
+        write!(formatter, "{}", self.error)?;
+        if let Some(ref value) = self.value {
+            if !self.pointer.is_empty() {
+                write!(formatter, " (got {})", value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+pub fn from_str<T>(s: &str) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    from_slice(s.as_bytes())
+}
+
+// The happy path is a plain `serde_json::from_slice`; the input is only
+// parsed again, with path tracking, once it is known to be broken.
+pub fn from_slice<T>(bytes: &[u8]) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    match serde_json::from_slice(bytes) {
+        Ok(value) => Ok(value),
+        Err(e) => Err(diagnose::<T>(bytes).unwrap_or(Error {
+            pointer: String::new(),
+            value: None,
+            error: e,
+        })),
+    }
+}
+
+fn diagnose<T>(bytes: &[u8]) -> Option<Error>
+where
+    T: DeserializeOwned,
+{
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+    let e = match serde_path_to_error::deserialize::<_, T>(&mut deserializer) {
+        Ok(_) => return None,
+        Err(e) => e,
+    };
+
+    let mut pointer = String::new();
+    for segment in e.path().iter() {
+        match *segment {
+            serde_path_to_error::Segment::Seq { index } => {
+                pointer.push('/');
+                pointer.push_str(&index.to_string());
+            }
+            serde_path_to_error::Segment::Map { ref key } => {
+                pointer.push('/');
+                pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+            }
+            serde_path_to_error::Segment::Enum { .. } | serde_path_to_error::Segment::Unknown => {}
+        }
+    }
+
+    let value = serde_json::from_slice::<Value>(bytes)
+        .ok()
+        .and_then(|v| v.pointer(&pointer).cloned());
+
+    Some(Error {
+        pointer,
+        value,
+        error: e.into_inner(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use current::{BidRequest, BidResponse};
+
+    #[test]
+    fn pointer_to_nested_value() {
+        let serialized = r#"{
+            "id": "1234",
+            "at": 1,
+            "imp": [
+                {"id": "1"},
+                {"id": "2", "video": {"mimes": ["video/mp4"], "protocols": [2, "3"]}}
+            ]
+        }"#;
+
+        let e = BidRequest::parse(serialized.as_bytes()).unwrap_err();
+
+        assert_eq!("/imp/1/video/protocols/1", e.pointer);
+        assert_eq!(Some(Value::from("3")), e.value);
+        assert_eq!(6, e.error.line());
+    }
+
+    #[test]
+    fn missing_field() {
+        let e = BidResponse::parse(br#"{"id": "1", "seatbid": [{"bid": [{"id": "1", "impid": "1"}]}]}"#)
+            .unwrap_err();
+
+        assert_eq!("/seatbid/0/bid/0", e.pointer);
+        assert!(e.to_string().contains("missing field `price`"));
+    }
+
+    #[test]
+    fn ignored_flag_does_not_hide_the_error() {
+        let serialized = r#"{
+            "id": "1234",
+            "at": 1,
+            "imp": [{"id": "1", "instl": "yes", "bidfloor": "0.5"}]
+        }"#;
+
+        let e = BidRequest::parse(serialized.as_bytes()).unwrap_err();
+
+        assert_eq!("/imp/0/bidfloor", e.pointer);
+    }
+
+    #[test]
+    fn syntax_error() {
+        let e = BidRequest::parse(br#"{"id": "1234", "#).unwrap_err();
+
+        assert_eq!("", e.pointer);
+        assert_eq!(None, e.value);
+        assert!(e.error.is_eof());
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::BTreeMap;
+
+use ext::Extension;
+use serde_utils;
+
+// bid.ext
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct BidExt {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prebid: Option<BidPrebid>,
+}
+
+// bid.ext.prebid
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct BidPrebid {
+    // The media type of the creative.
+    #[serde(rename = "type")]
+    pub type_: BidType,
+
+    // Ad server targeting keys (hb_pb, hb_bidder, ...) for this bid.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub targeting: BTreeMap<String, String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<BidCache>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video: Option<BidVideo>,
+
+    #[serde(flatten)]
+    pub other: serde_utils::Ext,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BidType {
+    #[default]
+    Banner,
+    Video,
+    Audio,
+    Native,
+}
+
+// Where Prebid Cache stored this bid.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct BidCache {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bids: Option<CacheEntry>,
+
+    #[serde(rename = "vastXml", skip_serializing_if = "Option::is_none")]
+    pub vast_xml: Option<CacheEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CacheEntry {
+    pub url: String,
+
+    #[serde(rename = "cacheId")]
+    pub cache_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct BidVideo {
+    // Duration of the creative in seconds.
+    pub duration: u32,
+
+    pub primary_category: String,
+}
+
+impl Extension for BidExt {}
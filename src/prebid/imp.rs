@@ -0,0 +1,52 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use serde::de::DeserializeOwned;
+use serde_json;
+
+use ext::Extension;
+use serde_utils;
+
+use super::request::StoredRequest;
+
+// imp.ext
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ImpExt {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prebid: Option<ImpPrebid>,
+}
+
+// imp.ext.prebid
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ImpPrebid {
+    // Params for each bidder that should bid on this imp, keyed by bidder
+    // name. Their shape is specific to each bidder.
+    #[serde(default, skip_serializing_if = "serde_utils::Ext::is_empty")]
+    pub bidder: serde_utils::Ext,
+
+    #[serde(rename = "storedrequest", skip_serializing_if = "Option::is_none")]
+    pub stored_request: Option<StoredRequest>,
+
+    #[serde(flatten)]
+    pub other: serde_utils::Ext,
+}
+
+impl ImpPrebid {
+    // Decodes the params of one bidder; `Ok(None)` if it has none.
+    pub fn bidder_params<T>(&self, bidder: &str) -> Result<Option<T>, serde_json::Error>
+    where
+        T: DeserializeOwned,
+    {
+        match self.bidder.get(bidder) {
+            Some(v) => serde_json::from_value(v.clone()).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl Extension for ImpExt {}
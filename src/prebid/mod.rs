@@ -0,0 +1,126 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Prebid Server's well-known extensions: `request.ext.prebid`,
+//! `imp.ext.prebid` and `bid.ext.prebid`.
+//!
+//! The `Prebid` trait reads and writes them on `BidRequest`, `Imp` and
+//! `Bid`, leaving the other keys of the ext map untouched. The keys of
+//! `prebid` that aren't modelled here, such as `bidders` or `floors`, are
+//! kept in its `other` map, so writing it back doesn't drop them.
+
+mod bid;
+mod imp;
+mod request;
+
+pub use self::bid::*;
+pub use self::imp::*;
+pub use self::request::*;
+
+use ext::{self, Extensible};
+use v2_5::{Bid, BidRequest, Imp};
+
+pub trait Prebid: Extensible {
+    type Ext;
+
+    fn prebid(&self) -> Result<Option<Self::Ext>, ext::Error>;
+
+    fn set_prebid(&mut self, prebid: Self::Ext) -> Result<(), ext::Error>;
+}
+
+macro_rules! prebid {
+    ( $( $t:ty => $wrapper:ident, $inner:ty );* ) => {
+        $(
+            impl Prebid for $t {
+                type Ext = $inner;
+
+                fn prebid(&self) -> Result<Option<$inner>, ext::Error> {
+                    Ok(self.ext_as::<$wrapper>()?.and_then(|x| x.prebid))
+                }
+
+                fn set_prebid(&mut self, prebid: $inner) -> Result<(), ext::Error> {
+                    self.set_ext(&$wrapper {
+                        prebid: Some(prebid),
+                    })
+                }
+            }
+        )*
+    }
+}
+
+prebid!(
+    BidRequest => RequestExt, RequestPrebid;
+    Imp => ImpExt, ImpPrebid;
+    Bid => BidExt, BidPrebid
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+    use v2_5::Price;
+
+    #[test]
+    fn round_trip_on_request() {
+        let serialized = r#"{
+            "id": "1234",
+            "at": 1,
+            "imp": [{
+                "id": "1",
+                "banner": {"w": 300, "h": 250},
+                "ext": {"prebid": {"bidder": {"appnexus": {"placementId": 13144370}}}}
+            }],
+            "ext": {
+                "prebid": {
+                    "targeting": {"pricegranularity": "med", "includewinners": true},
+                    "cache": {"bids": {}},
+                    "aliases": {"districtm": "appnexus"},
+                    "bidadjustmentfactors": {"appnexus": 0.9},
+                    "channel": {"name": "web", "version": "4.1"},
+                    "debug": true,
+                    "auctiontimestamp": 1539211289000,
+                    "floors": {"enabled": true}
+                },
+                "schain": {"ver": "1.0"}
+            }
+        }"#;
+
+        let mut req: BidRequest = serde_json::from_str(serialized).unwrap();
+        let prebid = req.prebid().unwrap().unwrap();
+        assert_eq!(Some(&"appnexus".to_string()), prebid.aliases.get("districtm"));
+        assert!(prebid.debug);
+        assert_eq!(1539211289000u64, prebid.other["auctiontimestamp"]);
+
+        let imp = req.imp[0].prebid().unwrap().unwrap();
+        let params: serde_json::Value = imp.bidder_params("appnexus").unwrap().unwrap();
+        assert_eq!(13144370, params["placementId"]);
+
+        let expected = req.ext.clone();
+        req.set_prebid(prebid).unwrap();
+        assert_eq!(expected, req.ext);
+    }
+
+    #[test]
+    fn set_on_bid() {
+        let mut bid = Bid::new("1".to_string(), "1".to_string(), Price::from_micros(1_500_000));
+        bid.set_prebid(BidPrebid {
+            type_: BidType::Video,
+            video: Some(BidVideo {
+                duration: 30,
+                primary_category: String::new(),
+            }),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(
+            r#"{"id":"1","impid":"1","price":1.5,"ext":{"prebid":{"type":"video","video":{"duration":30,"primary_category":""}}}}"#,
+            serde_json::to_string(&bid).unwrap()
+        );
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::BTreeMap;
+
+use ext::Extension;
+use serde_utils;
+
+// request.ext
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RequestExt {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prebid: Option<RequestPrebid>,
+}
+
+// request.ext.prebid
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RequestPrebid {
+    // Bidder aliases, from alias to the core bidder it stands for.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+
+    // Multipliers applied to each bidder's bid prices.
+    #[serde(
+        rename = "bidadjustmentfactors",
+        default,
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub bid_adjustment_factors: BTreeMap<String, f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<Cache>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<Channel>,
+
+    #[serde(default, skip_serializing_if = "serde_utils::is_false")]
+    pub debug: bool,
+
+    #[serde(rename = "storedrequest", skip_serializing_if = "Option::is_none")]
+    pub stored_request: Option<StoredRequest>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub targeting: Option<Targeting>,
+
+    #[serde(flatten)]
+    pub other: serde_utils::Ext,
+}
+
+// Asks Prebid Server to put bids and/or VAST XML in Prebid Cache.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Cache {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bids: Option<CacheOptions>,
+
+    #[serde(rename = "vastxml", skip_serializing_if = "Option::is_none")]
+    pub vast_xml: Option<CacheOptions>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CacheOptions {
+    // Whether the creative is returned in the response in addition to
+    // being cached.
+    #[serde(rename = "returnCreative", skip_serializing_if = "Option::is_none")]
+    pub return_creative: Option<bool>,
+}
+
+// The kind of client the request came from, e.g. "web" or "app".
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Channel {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+// Reference to a request or imp stored on the Prebid Server host.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct StoredRequest {
+    pub id: String,
+}
+
+// Asks Prebid Server to add ad server targeting keys to the bids.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Targeting {
+    #[serde(rename = "pricegranularity", skip_serializing_if = "Option::is_none")]
+    pub price_granularity: Option<PriceGranularity>,
+
+    #[serde(rename = "includewinners", skip_serializing_if = "Option::is_none")]
+    pub include_winners: Option<bool>,
+
+    #[serde(rename = "includebidderkeys", skip_serializing_if = "Option::is_none")]
+    pub include_bidder_keys: Option<bool>,
+
+    #[serde(rename = "includeformat", skip_serializing_if = "Option::is_none")]
+    pub include_format: Option<bool>,
+
+    #[serde(rename = "preferdeals", skip_serializing_if = "Option::is_none")]
+    pub prefer_deals: Option<bool>,
+
+    // Allowed video durations in seconds, for long-form video.
+    #[serde(rename = "durationrangesec", default, skip_serializing_if = "Vec::is_empty")]
+    pub duration_range_sec: Vec<u32>,
+}
+
+// Either one of the named granularities ("low", "med", "high", "auto",
+// "dense") or a custom set of price buckets.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum PriceGranularity {
+    Named(String),
+    Custom {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        precision: Option<u32>,
+        ranges: Vec<PriceRange>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PriceRange {
+    #[serde(default)]
+    pub min: f64,
+    pub max: f64,
+    pub increment: f64,
+}
+
+impl Extension for RequestExt {
+    fn validate(&self) -> Result<(), String> {
+        let prebid = match self.prebid {
+            Some(ref p) => p,
+            None => return Ok(()),
+        };
+
+        for (alias, core) in &prebid.aliases {
+            if alias == core {
+                return Err(format!("alias {} can't point to itself", alias));
+            }
+        }
+
+        for (bidder, factor) in &prebid.bid_adjustment_factors {
+            if *factor <= 0.0 {
+                return Err(format!("bid adjustment factor for {} must be positive", bidder));
+            }
+        }
+
+        if let Some(PriceGranularity::Custom { ref ranges, .. }) = prebid
+            .targeting
+            .as_ref()
+            .and_then(|t| t.price_granularity.as_ref())
+        {
+            let mut prev_max = 0.0;
+            for r in ranges {
+                if r.increment <= 0.0 || r.max <= r.min || r.min < prev_max {
+                    return Err(format!("invalid price granularity range {:?}", r));
+                }
+                prev_max = r.max;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn custom_price_granularity() {
+        let serialized = r#"{"precision":2,"ranges":[{"max":5.0,"increment":0.05},{"min":5.0,"max":20.0,"increment":0.5}]}"#;
+        let g: PriceGranularity = serde_json::from_str(serialized).unwrap();
+
+        match g {
+            PriceGranularity::Custom { ref ranges, .. } => assert_eq!(2, ranges.len()),
+            ref g => panic!("{:?}", g),
+        }
+    }
+
+    #[test]
+    fn validate_rejects_overlapping_ranges() {
+        let serialized = r#"{"prebid":{"targeting":{"pricegranularity":{"ranges":[
+            {"max":5.0,"increment":0.05},
+            {"min":4.0,"max":20.0,"increment":0.5}
+        ]}}}}"#;
+        let ext: RequestExt = serde_json::from_str(serialized).unwrap();
+
+        assert!(ext.validate().is_err())
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Bit-level reading of the base64url-encoded privacy strings.
+
+use std::collections::BTreeSet;
+
+// A set of IDs kept as the sorted, disjoint ranges they form, so that a
+// short string listing wide ranges stays small once decoded.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IdSet {
+    ranges: Vec<(u16, u16)>,
+}
+
+impl IdSet {
+    // Inclusive ranges in any order, possibly overlapping.
+    fn from_ranges(mut ranges: Vec<(u16, u16)>) -> IdSet {
+        ranges.sort_unstable();
+        let mut merged: Vec<(u16, u16)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if u32::from(start) <= u32::from(last.1) + 1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        IdSet { ranges: merged }
+    }
+
+    pub fn contains(&self, id: u16) -> bool {
+        let i = self.ranges.partition_point(|&(_, end)| end < id);
+        self.ranges.get(i).is_some_and(|&(start, _)| start <= id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u16> + '_ {
+        self.ranges.iter().flat_map(|&(start, end)| start..=end)
+    }
+}
+
+// Decodes base64url, with or without padding. Trailing bits that don't make
+// a whole byte are kept as a final zero-padded byte, since the privacy
+// strings are bit streams rather than byte streams.
+pub fn decode_base64url(s: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(s.len() * 3 / 4 + 1);
+    let mut acc: u32 = 0;
+    let mut len = 0;
+    for c in s.trim_end_matches('=').bytes() {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'-' | b'+' => 62,
+            b'_' | b'/' => 63,
+            _ => return None,
+        };
+        acc = (acc << 6) | u32::from(v);
+        len += 6;
+        if len >= 8 {
+            len -= 8;
+            bytes.push((acc >> len) as u8);
+            acc &= (1 << len) - 1;
+        }
+    }
+    if len > 0 {
+        bytes.push((acc << (8 - len)) as u8);
+    }
+    Some(bytes)
+}
+
+pub struct Bits<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Bits<'a> {
+    pub fn new(bytes: &'a [u8]) -> Bits<'a> {
+        Bits { bytes, pos: 0 }
+    }
+
+    pub fn remaining(&self) -> usize {
+        (self.bytes.len() * 8).saturating_sub(self.pos)
+    }
+
+    // Reads an `n`-bit big-endian integer, n <= 64; `None` past the end.
+    pub fn read(&mut self, n: usize) -> Option<u64> {
+        if n > self.remaining() {
+            return None;
+        }
+        let mut v = 0u64;
+        for _ in 0..n {
+            let bit = (self.bytes[self.pos / 8] >> (7 - self.pos % 8)) & 1;
+            v = (v << 1) | u64::from(bit);
+            self.pos += 1;
+        }
+        Some(v)
+    }
+
+    pub fn read_bool(&mut self) -> Option<bool> {
+        self.read(1).map(|b| b == 1)
+    }
+
+    // `n` one-bit flags for the IDs 1 to n; returns the IDs that are set.
+    pub fn read_bitfield(&mut self, n: usize) -> Option<BTreeSet<u16>> {
+        let mut set = BTreeSet::new();
+        for id in 1..=n {
+            if self.read_bool()? {
+                set.insert(id as u16);
+            }
+        }
+        Some(set)
+    }
+
+    // A 12-bit count followed by that many entries, each a single 16-bit
+    // ID or, if its leading flag is set, an inclusive range of them.
+    // `None` for a range that ends before it starts or an ID above `max`.
+    pub fn read_ranges(&mut self, max: u16) -> Option<IdSet> {
+        let mut ranges = vec![];
+        for _ in 0..self.read(12)? {
+            let is_range = self.read_bool()?;
+            let start = self.read(16)? as u16;
+            let end = if is_range { self.read(16)? as u16 } else { start };
+            if start == 0 || end < start || end > max {
+                return None;
+            }
+            ranges.push((start, end));
+        }
+        Some(IdSet::from_ranges(ranges))
+    }
+
+    // The vendor section shared by several segments: a 16-bit maximum ID,
+    // then either a bitfield up to it or a list of ranges.
+    pub fn read_vendors(&mut self) -> Option<IdSet> {
+        let max = self.read(16)? as u16;
+        if self.read_bool()? {
+            return self.read_ranges(max);
+        }
+        let mut ranges: Vec<(u16, u16)> = vec![];
+        for id in 1..=max {
+            if !self.read_bool()? {
+                continue;
+            }
+            match ranges.last_mut() {
+                Some(last) if last.1 + 1 == id => last.1 = id,
+                _ => ranges.push((id, id)),
+            }
+        }
+        Some(IdSet { ranges })
+    }
+
+    // A Fibonacci-coded integer: bit i stands for the (i + 2)th Fibonacci
+    // number (1, 2, 3, 5, ...) and two consecutive ones end the code.
+    pub fn read_fibonacci(&mut self) -> Option<u64> {
+        let (mut a, mut b) = (1u64, 2u64);
+        let mut value = 0;
+        let mut previous = false;
+        loop {
+            let bit = self.read_bool()?;
+            if bit && previous {
+                return Some(value);
+            }
+            if bit {
+                value = value.checked_add(a)?;
+            }
+            previous = bit;
+            let next = a.checked_add(b)?;
+            a = b;
+            b = next;
+        }
+    }
+
+    // Like `read_ranges`, with Fibonacci-coded IDs, each an offset from
+    // the previous one.
+    pub fn read_fibonacci_ranges(&mut self) -> Option<Vec<u16>> {
+        let mut ids = vec![];
+        let mut last = 0u64;
+        for _ in 0..self.read(12)? {
+            let is_range = self.read_bool()?;
+            let start = last.checked_add(self.read_fibonacci()?)?;
+            let end = if is_range {
+                start.checked_add(self.read_fibonacci()?)?
+            } else {
+                start
+            };
+            if end > u64::from(u16::MAX) {
+                return None;
+            }
+            ids.extend(start as u16..=end as u16);
+            last = end;
+        }
+        Some(ids)
+    }
+
+    // Six-bit letters, 0 = 'A'.
+    pub fn read_letters(&mut self, n: usize) -> Option<String> {
+        let mut s = String::with_capacity(n);
+        for _ in 0..n {
+            s.push(char::from(b'A' + self.read(6)? as u8));
+        }
+        Some(s)
+    }
+}
+
+// Builds bit strings for the tests, the reverse of `Bits`.
+#[cfg(test)]
+#[derive(Default)]
+pub struct BitWriter {
+    bits: Vec<bool>,
+}
+
+#[cfg(test)]
+impl BitWriter {
+    pub fn write(&mut self, n: usize, v: u64) -> &mut Self {
+        for i in (0..n).rev() {
+            self.bits.push((v >> i) & 1 == 1);
+        }
+        self
+    }
+
+    pub fn write_ids(&mut self, n: usize, ids: &[u16]) -> &mut Self {
+        for id in 1..=n {
+            self.bits.push(ids.contains(&(id as u16)));
+        }
+        self
+    }
+
+    pub fn write_fibonacci(&mut self, mut v: u64) -> &mut Self {
+        let mut fib = vec![1u64, 2];
+        while fib[fib.len() - 1] <= v {
+            let next = fib[fib.len() - 1] + fib[fib.len() - 2];
+            fib.push(next);
+        }
+        let mut code = vec![false; fib.len()];
+        for i in (0..fib.len()).rev() {
+            if fib[i] <= v {
+                v -= fib[i];
+                code[i] = true;
+            }
+        }
+        while code.last() == Some(&false) {
+            code.pop();
+        }
+        self.bits.extend(code);
+        self.bits.push(true);
+        self
+    }
+
+    pub fn to_base64url(&self) -> String {
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+        self.bits
+            .chunks(6)
+            .map(|chunk| {
+                let v = (0..6).fold(0, |v, i| (v << 1) | chunk.get(i).map_or(0, |&b| b as usize));
+                char::from(ALPHABET[v])
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,369 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Data minimisation: removes from a bid request what its privacy signals
+//! say may not be passed on, and reports what was removed and why.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use super::PrivacySignals;
+#[cfg(feature = "unknown-fields")]
+use serde_utils;
+use v2_5::{BidRequest, Device, User};
+
+// What to remove when a rule applies. With the `unknown-fields` feature,
+// the keys outside the spec kept on the user, device and geo objects go
+// with their ids, since nothing says what they hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Scrub {
+    // user.id, user.buyeruid, user.eids and user.ext.eids.
+    pub user_ids: bool,
+    // user.data.
+    pub user_data: bool,
+    // device.ifa and the hashed device and MAC ids.
+    pub device_ids: bool,
+    // Zeroes the last octet of device.ip and the last 80 bits of
+    // device.ipv6.
+    pub truncate_ip: bool,
+    // device.geo.lat and device.geo.lon.
+    pub precise_geo: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Policy {
+    // Applied when regs.coppa is set.
+    pub coppa: Scrub,
+    // Applied when regs.ext.gdpr is 1 and the consent string doesn't allow
+    // `gdpr_vendor_id` (or, without one, the user) the `gdpr_purposes`.
+    pub gdpr: Scrub,
+    pub gdpr_vendor_id: Option<u16>,
+    pub gdpr_purposes: Vec<u16>,
+    // Applied when the user opted out of sale or sharing under a US law.
+    pub us_opt_out: Scrub,
+    // Applied when device.lmt or device.dnt is set.
+    pub lmt: Scrub,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    Coppa,
+    Gdpr,
+    UsOptOut,
+    LimitAdTracking,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Report {
+    // The rules that applied.
+    pub reasons: Vec<Reason>,
+    // JSON pointers of the values removed or truncated, e.g. "/device/ifa".
+    pub removed: Vec<String>,
+}
+
+impl Scrub {
+    pub fn all() -> Scrub {
+        Scrub {
+            user_ids: true,
+            user_data: true,
+            device_ids: true,
+            truncate_ip: true,
+            precise_geo: true,
+        }
+    }
+
+    fn union(self, other: Scrub) -> Scrub {
+        Scrub {
+            user_ids: self.user_ids || other.user_ids,
+            user_data: self.user_data || other.user_data,
+            device_ids: self.device_ids || other.device_ids,
+            truncate_ip: self.truncate_ip || other.truncate_ip,
+            precise_geo: self.precise_geo || other.precise_geo,
+        }
+    }
+}
+
+// Every rule scrubs everything, and GDPR requires consent to purpose 1
+// (storing and accessing information on the device).
+impl Default for Policy {
+    fn default() -> Policy {
+        Policy {
+            coppa: Scrub::all(),
+            gdpr: Scrub::all(),
+            gdpr_vendor_id: None,
+            gdpr_purposes: vec![1],
+            us_opt_out: Scrub::all(),
+            lmt: Scrub::all(),
+        }
+    }
+}
+
+impl Report {
+    pub fn is_empty(&self) -> bool {
+        self.reasons.is_empty()
+    }
+}
+
+impl Policy {
+    // The rules that apply to `request`, in the order of `Reason`.
+    pub fn reasons<E>(&self, request: &BidRequest<E>) -> Vec<Reason> {
+        let signals = PrivacySignals::from_request(request);
+        let mut reasons = vec![];
+
+        if signals.coppa {
+            reasons.push(Reason::Coppa);
+        }
+        if signals.gdpr == Some(true) && !self.gdpr_allowed(&signals) {
+            reasons.push(Reason::Gdpr);
+        }
+        if signals.opted_out() {
+            reasons.push(Reason::UsOptOut);
+        }
+        let device = request.device.as_ref();
+        if device.is_some_and(|d| d.lmt == Some(true) || d.dnt == Some(true)) {
+            reasons.push(Reason::LimitAdTracking);
+        }
+        reasons
+    }
+
+    // Scrubs `request` in place.
+    pub fn enforce<E>(&self, request: &mut BidRequest<E>) -> Report {
+        let reasons = self.reasons(request);
+        let scrub = reasons.iter().fold(Scrub::default(), |scrub, reason| {
+            scrub.union(match *reason {
+                Reason::Coppa => self.coppa,
+                Reason::Gdpr => self.gdpr,
+                Reason::UsOptOut => self.us_opt_out,
+                Reason::LimitAdTracking => self.lmt,
+            })
+        });
+
+        let mut removed = vec![];
+        if let Some(ref mut user) = request.user {
+            scrub_user(user, scrub, &mut removed);
+        }
+        if let Some(ref mut device) = request.device {
+            scrub_device(device, scrub, &mut removed);
+        }
+        Report { reasons, removed }
+    }
+
+    fn gdpr_allowed(&self, signals: &PrivacySignals) -> bool {
+        let tc = match signals.tcf {
+            Some(ref tc) => tc,
+            None => return false,
+        };
+        match self.gdpr_vendor_id {
+            Some(vendor_id) => tc.vendor_allowed(vendor_id, &self.gdpr_purposes),
+            None => self.gdpr_purposes.iter().all(|p| tc.purposes_consent.contains(p)),
+        }
+    }
+}
+
+// Scrubs `request` with the default policy.
+pub fn enforce<E>(request: &mut BidRequest<E>) -> Report {
+    Policy::default().enforce(request)
+}
+
+fn take<T>(field: &mut Option<T>, path: &str, removed: &mut Vec<String>) {
+    if field.take().is_some() {
+        removed.push(path.to_string());
+    }
+}
+
+fn scrub_user(user: &mut User, scrub: Scrub, removed: &mut Vec<String>) {
+    if scrub.user_ids {
+        take(&mut user.id, "/user/id", removed);
+        take(&mut user.buyer_uid, "/user/buyeruid", removed);
+        if !user.eids.is_empty() {
+            user.eids.clear();
+            removed.push("/user/eids".to_string());
+        }
+        if user.ext.as_mut().and_then(|ext| ext.remove("eids")).is_some() {
+            removed.push("/user/ext/eids".to_string());
+        }
+        #[cfg(feature = "unknown-fields")]
+        clear_other(&mut user.other, "/user", removed);
+    }
+    if scrub.user_data {
+        take(&mut user.data, "/user/data", removed);
+    }
+}
+
+fn scrub_device(device: &mut Device, scrub: Scrub, removed: &mut Vec<String>) {
+    if scrub.device_ids {
+        take(&mut device.ifa, "/device/ifa", removed);
+        take(&mut device.didsha1, "/device/didsha1", removed);
+        take(&mut device.didmd5, "/device/didmd5", removed);
+        take(&mut device.dpidsha1, "/device/dpidsha1", removed);
+        take(&mut device.dpidmd5, "/device/dpidmd5", removed);
+        take(&mut device.macsha1, "/device/macsha1", removed);
+        take(&mut device.macmd5, "/device/macmd5", removed);
+        #[cfg(feature = "unknown-fields")]
+        clear_other(&mut device.other, "/device", removed);
+    }
+    if scrub.truncate_ip {
+        truncate_ip(&mut device.ip, "/device/ip", removed);
+        truncate_ip(&mut device.ipv6, "/device/ipv6", removed);
+    }
+    if scrub.precise_geo {
+        if let Some(ref mut geo) = device.geo {
+            take(&mut geo.lat, "/device/geo/lat", removed);
+            take(&mut geo.lon, "/device/geo/lon", removed);
+            #[cfg(feature = "unknown-fields")]
+            clear_other(&mut geo.other, "/device/geo", removed);
+        }
+    }
+}
+
+#[cfg(feature = "unknown-fields")]
+fn clear_other(other: &mut serde_utils::Ext, path: &str, removed: &mut Vec<String>) {
+    for key in std::mem::take(other).keys() {
+        removed.push(format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1")));
+    }
+}
+
+// Truncates an address in place; one that can't be parsed is removed.
+fn truncate_ip(field: &mut Option<String>, path: &str, removed: &mut Vec<String>) {
+    let truncated = match *field {
+        Some(ref ip) => match ip.parse::<IpAddr>() {
+            Ok(IpAddr::V4(v4)) => {
+                let o = v4.octets();
+                Some(Ipv4Addr::new(o[0], o[1], o[2], 0).to_string())
+            }
+            Ok(IpAddr::V6(v6)) => {
+                let s = v6.segments();
+                Some(Ipv6Addr::new(s[0], s[1], s[2], 0, 0, 0, 0, 0).to_string())
+            }
+            Err(_) => None,
+        },
+        None => return,
+    };
+    if *field != truncated {
+        *field = truncated;
+        removed.push(path.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    fn request(regs: &str, device: &str) -> BidRequest {
+        serde_json::from_str(&format!(
+            r#"{{
+                "id": "1",
+                "imp": [],
+                "at": 1,
+                "regs": {},
+                "device": {},
+                "user": {{
+                    "id": "u1",
+                    "buyeruid": "b1",
+                    "eids": [{{"source": "uidapi.com", "uids": [{{"id": "x"}}]}}],
+                    "data": [{{"name": "dmp", "segment": []}}],
+                    "ext": {{"consent": "", "eids": []}}
+                }}
+            }}"#,
+            regs, device
+        ))
+        .unwrap()
+    }
+
+    const DEVICE: &str = r#"{
+        "ifa": "AA000DFE-74EA-4E6D-B3C0-DE39D2AD3C5C",
+        "ip": "192.168.1.8",
+        "ipv6": "2001:db8:85a3:8d3:1319:8a2e:370:7348",
+        "geo": {"lat": 48.8566, "lon": 2.3522, "country": "FRA"}
+    }"#;
+
+    #[test]
+    fn coppa() {
+        let mut b = request(r#"{"coppa": 1}"#, DEVICE);
+        let report = enforce(&mut b);
+
+        assert_eq!(vec![Reason::Coppa], report.reasons);
+        let user = b.user.unwrap();
+        assert_eq!(None, user.id);
+        assert!(user.eids.is_empty());
+        assert!(!user.ext.unwrap().contains_key("eids"));
+        let device = b.device.unwrap();
+        assert_eq!(None, device.ifa);
+        assert_eq!(Some("192.168.1.0".to_string()), device.ip);
+        assert_eq!(Some("2001:db8:85a3::".to_string()), device.ipv6);
+        let geo = device.geo.unwrap();
+        assert_eq!(None, geo.lat);
+        assert_eq!(Some("FRA".to_string()), geo.country);
+        assert!(report.removed.contains(&"/device/geo/lon".to_string()));
+    }
+
+    #[cfg(feature = "unknown-fields")]
+    #[test]
+    fn unknown_fields() {
+        let device = r#"{"ifa": "x", "sua": {"model": "Pixel"}, "geo": {"lat": 1.0, "h3": "8a1fb46622dffff"}}"#;
+        let mut b = request(r#"{"coppa": 1}"#, device);
+        b.user.as_mut().unwrap().other.insert("consent".to_string(), "CP...".into());
+        let report = enforce(&mut b);
+
+        assert!(b.user.unwrap().other.is_empty());
+        let device = b.device.unwrap();
+        assert!(device.other.is_empty());
+        assert!(device.geo.unwrap().other.is_empty());
+        for path in &["/user/consent", "/device/sua", "/device/geo/h3"] {
+            assert!(report.removed.contains(&path.to_string()), "{}", path);
+        }
+    }
+
+    #[test]
+    fn nothing_applies() {
+        let mut b = request(r#"{"ext": {"gdpr": 0, "us_privacy": "1YNN"}}"#, DEVICE);
+        let before = b.clone();
+        let report = enforce(&mut b);
+
+        assert!(report.is_empty());
+        assert_eq!(before, b);
+    }
+
+    #[test]
+    fn gdpr_without_consent() {
+        let mut b = request(r#"{"ext": {"gdpr": 1}}"#, DEVICE);
+        let report = enforce(&mut b);
+
+        assert_eq!(vec![Reason::Gdpr], report.reasons);
+        assert_eq!(None, b.device.unwrap().ifa);
+    }
+
+    #[test]
+    fn configurable_policy() {
+        let policy = Policy {
+            us_opt_out: Scrub {
+                user_ids: true,
+                ..Scrub::default()
+            },
+            ..Policy::default()
+        };
+        let mut b = request(r#"{"ext": {"us_privacy": "1YYN"}}"#, r#"{"lmt": 0, "ifa": "x"}"#);
+        let report = policy.enforce(&mut b);
+
+        assert_eq!(vec![Reason::UsOptOut], report.reasons);
+        assert_eq!(
+            vec!["/user/id", "/user/buyeruid", "/user/eids", "/user/ext/eids"],
+            report.removed
+        );
+        assert_eq!(Some("x".to_string()), b.device.unwrap().ifa);
+    }
+
+    #[test]
+    fn limit_ad_tracking() {
+        let mut b = request("{}", r#"{"lmt": 1, "ip": "not an ip"}"#);
+        let report = enforce(&mut b);
+
+        assert_eq!(vec![Reason::LimitAdTracking], report.reasons);
+        assert_eq!(None, b.device.unwrap().ip);
+    }
+}
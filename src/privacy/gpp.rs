@@ -0,0 +1,474 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! IAB Global Privacy Platform strings: a header listing section IDs,
+//! followed by one '~'-separated string per section.
+//!
+//! The TCF EU v2, US Privacy, US National and US state (California,
+//! Virginia, Colorado, Utah, Connecticut) sections are decoded; the others
+//! are kept as sent.
+
+use std::error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use super::bits::{decode_base64url, Bits};
+use super::tcf::{self, TcString};
+use super::us_privacy::{self, Signal, UsPrivacy};
+
+pub const TCF_EU_V2: u16 = 2;
+pub const US_PRIVACY: u16 = 6;
+pub const US_NATIONAL: u16 = 7;
+pub const US_CALIFORNIA: u16 = 8;
+pub const US_VIRGINIA: u16 = 9;
+pub const US_COLORADO: u16 = 10;
+pub const US_UTAH: u16 = 11;
+pub const US_CONNECTICUT: u16 = 12;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    // The header or a US section isn't valid base64url.
+    Base64,
+    // The header isn't a GPP v1 header.
+    InvalidHeader,
+    // The header lists a different number of sections than the string
+    // carries.
+    SectionCount { expected: usize, found: usize },
+    Tcf(tcf::Error),
+    UsPrivacy(us_privacy::Error),
+    // A US section is truncated, has an unknown version or an out of
+    // range value.
+    InvalidSection(u16),
+    // An entry of regs.ext.gpp_sid isn't a section ID.
+    InvalidSid(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Base64 => formatter.write_str("GPP string is not base64url"),
+            Error::InvalidHeader => formatter.write_str("invalid GPP header"),
+            Error::SectionCount { expected, found } => write!(
+                formatter,
+                "GPP header lists {} sections, found {}",
+                expected, found
+            ),
+            Error::Tcf(ref e) => write!(formatter, "GPP TCF EU v2 section: {}", e),
+            Error::UsPrivacy(ref e) => write!(formatter, "GPP US Privacy section: {}", e),
+            Error::InvalidSection(id) => write!(formatter, "invalid GPP section {}", id),
+            Error::InvalidSid(ref s) => write!(formatter, "invalid GPP section ID {}", s),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Tcf(ref e) => Some(e),
+            Error::UsPrivacy(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// A US National or state section. Fields a section doesn't define are
+// `None` or empty. Two-bit fields map 0, 1 and 2 to `NotApplicable`, `Yes`
+// and `No`: for a notice, Yes means it was given; for an opt-out, that the
+// user opted out.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UsSection {
+    pub section_id: u16,
+    pub version: u8,
+    pub sharing_notice: Option<Signal>,
+    pub sale_opt_out_notice: Option<Signal>,
+    pub sharing_opt_out_notice: Option<Signal>,
+    pub targeted_advertising_opt_out_notice: Option<Signal>,
+    pub sensitive_data_processing_opt_out_notice: Option<Signal>,
+    pub sensitive_data_limit_use_notice: Option<Signal>,
+    pub sale_opt_out: Option<Signal>,
+    pub sharing_opt_out: Option<Signal>,
+    pub targeted_advertising_opt_out: Option<Signal>,
+    // Raw values per sensitive data category, whose meaning (consent or
+    // opt-out) depends on the state.
+    pub sensitive_data_processing: Vec<u8>,
+    pub known_child_sensitive_data_consents: Vec<u8>,
+    pub personal_data_consents: Option<Signal>,
+    pub mspa_covered_transaction: Option<Signal>,
+    pub mspa_opt_out_option_mode: Option<Signal>,
+    pub mspa_service_provider_mode: Option<Signal>,
+    // The Global Privacy Control subsection, if sent.
+    pub gpc: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Section {
+    TcfEuV2(Box<TcString>),
+    UsPrivacy(UsPrivacy),
+    Us(UsSection),
+    // A section this module doesn't decode, with its ID and string.
+    Other(u16, String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gpp {
+    pub version: u8,
+    // Sections in header order.
+    pub sections: Vec<Section>,
+}
+
+impl Section {
+    pub fn id(&self) -> u16 {
+        match *self {
+            Section::TcfEuV2(_) => TCF_EU_V2,
+            Section::UsPrivacy(_) => US_PRIVACY,
+            Section::Us(ref s) => s.section_id,
+            Section::Other(id, _) => id,
+        }
+    }
+}
+
+impl UsSection {
+    pub fn opted_out_of_sale(&self) -> bool {
+        self.sale_opt_out == Some(Signal::Yes)
+    }
+
+    // Opted out of sharing, or of the targeted advertising that sharing
+    // enables.
+    pub fn opted_out_of_sharing(&self) -> bool {
+        self.sharing_opt_out == Some(Signal::Yes) || self.targeted_advertising_opt_out == Some(Signal::Yes)
+    }
+}
+
+impl Gpp {
+    pub fn section_ids(&self) -> Vec<u16> {
+        self.sections.iter().map(Section::id).collect()
+    }
+
+    pub fn section(&self, id: u16) -> Option<&Section> {
+        self.sections.iter().find(|s| s.id() == id)
+    }
+
+    pub fn tcf_eu_v2(&self) -> Option<&TcString> {
+        self.sections.iter().find_map(|s| match *s {
+            Section::TcfEuV2(ref tc) => Some(&**tc),
+            _ => None,
+        })
+    }
+
+    pub fn us_privacy(&self) -> Option<&UsPrivacy> {
+        self.sections.iter().find_map(|s| match *s {
+            Section::UsPrivacy(ref usp) => Some(usp),
+            _ => None,
+        })
+    }
+
+    pub fn us_sections(&self) -> impl Iterator<Item = &UsSection> {
+        self.sections.iter().filter_map(|s| match *s {
+            Section::Us(ref us) => Some(us),
+            _ => None,
+        })
+    }
+}
+
+impl FromStr for Gpp {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Gpp, Error> {
+        let mut parts = s.trim().split('~');
+        let header = decode_base64url(parts.next().unwrap_or("")).ok_or(Error::Base64)?;
+        let mut bits = Bits::new(&header);
+        if bits.read(6) != Some(3) {
+            return Err(Error::InvalidHeader);
+        }
+        let version = bits.read(6).ok_or(Error::InvalidHeader)? as u8;
+        let ids = bits.read_fibonacci_ranges().ok_or(Error::InvalidHeader)?;
+
+        let strings: Vec<&str> = parts.collect();
+        if strings.len() != ids.len() {
+            return Err(Error::SectionCount {
+                expected: ids.len(),
+                found: strings.len(),
+            });
+        }
+
+        let sections = ids
+            .into_iter()
+            .zip(strings)
+            .map(|(id, s)| decode_section(id, s))
+            .collect::<Result<_, _>>()?;
+        Ok(Gpp { version, sections })
+    }
+}
+
+fn decode_section(id: u16, s: &str) -> Result<Section, Error> {
+    match id {
+        TCF_EU_V2 => s.parse().map(|tc| Section::TcfEuV2(Box::new(tc))).map_err(Error::Tcf),
+        US_PRIVACY => s.parse().map(Section::UsPrivacy).map_err(Error::UsPrivacy),
+        US_NATIONAL..=US_CONNECTICUT => decode_us(id, s).map(Section::Us),
+        _ => Ok(Section::Other(id, s.to_string())),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Field {
+    SharingNotice,
+    SaleOptOutNotice,
+    SharingOptOutNotice,
+    TargetedAdvertisingOptOutNotice,
+    SensitiveDataProcessingOptOutNotice,
+    SensitiveDataLimitUseNotice,
+    SaleOptOut,
+    SharingOptOut,
+    TargetedAdvertisingOptOut,
+    SensitiveDataProcessing(usize),
+    KnownChildSensitiveDataConsents(usize),
+    PersonalDataConsents,
+    MspaCoveredTransaction,
+    MspaOptOutOptionMode,
+    MspaServiceProviderMode,
+}
+
+// The core subsection of each US section, field by field, and whether a
+// GPC subsection may follow.
+fn layout(id: u16, version: u8) -> Option<(Vec<Field>, bool)> {
+    use self::Field::*;
+
+    let (fields, gpc) = match (id, version) {
+        (US_NATIONAL, 1) | (US_NATIONAL, 2) => (
+            vec![
+                SharingNotice,
+                SaleOptOutNotice,
+                SharingOptOutNotice,
+                TargetedAdvertisingOptOutNotice,
+                SensitiveDataProcessingOptOutNotice,
+                SensitiveDataLimitUseNotice,
+                SaleOptOut,
+                SharingOptOut,
+                TargetedAdvertisingOptOut,
+                SensitiveDataProcessing(if version == 1 { 12 } else { 16 }),
+                KnownChildSensitiveDataConsents(if version == 1 { 2 } else { 3 }),
+                PersonalDataConsents,
+            ],
+            true,
+        ),
+        (US_CALIFORNIA, 1) => (
+            vec![
+                SaleOptOutNotice,
+                SharingOptOutNotice,
+                SensitiveDataLimitUseNotice,
+                SaleOptOut,
+                SharingOptOut,
+                SensitiveDataProcessing(9),
+                KnownChildSensitiveDataConsents(2),
+                PersonalDataConsents,
+            ],
+            true,
+        ),
+        (US_VIRGINIA, 1) => (
+            vec![
+                SharingNotice,
+                SaleOptOutNotice,
+                TargetedAdvertisingOptOutNotice,
+                SaleOptOut,
+                TargetedAdvertisingOptOut,
+                SensitiveDataProcessing(8),
+                KnownChildSensitiveDataConsents(1),
+            ],
+            false,
+        ),
+        (US_COLORADO, 1) => (
+            vec![
+                SharingNotice,
+                SaleOptOutNotice,
+                TargetedAdvertisingOptOutNotice,
+                SaleOptOut,
+                TargetedAdvertisingOptOut,
+                SensitiveDataProcessing(7),
+                KnownChildSensitiveDataConsents(1),
+            ],
+            true,
+        ),
+        (US_UTAH, 1) => (
+            vec![
+                SharingNotice,
+                SaleOptOutNotice,
+                TargetedAdvertisingOptOutNotice,
+                SensitiveDataProcessingOptOutNotice,
+                SaleOptOut,
+                TargetedAdvertisingOptOut,
+                SensitiveDataProcessing(8),
+                KnownChildSensitiveDataConsents(1),
+            ],
+            false,
+        ),
+        (US_CONNECTICUT, 1) => (
+            vec![
+                SharingNotice,
+                SaleOptOutNotice,
+                TargetedAdvertisingOptOutNotice,
+                SaleOptOut,
+                TargetedAdvertisingOptOut,
+                SensitiveDataProcessing(8),
+                KnownChildSensitiveDataConsents(3),
+            ],
+            true,
+        ),
+        _ => return None,
+    };
+
+    // Every US section ends with the MSPA fields.
+    let mut fields = fields;
+    fields.extend_from_slice(&[MspaCoveredTransaction, MspaOptOutOptionMode, MspaServiceProviderMode]);
+    Some((fields, gpc))
+}
+
+fn decode_us(id: u16, s: &str) -> Result<UsSection, Error> {
+    let mut subsections = s.split('.');
+    let core = decode_base64url(subsections.next().unwrap_or("")).ok_or(Error::Base64)?;
+    let mut bits = Bits::new(&core);
+    let version = bits.read(6).ok_or(Error::InvalidSection(id))? as u8;
+    let (fields, has_gpc) = layout(id, version).ok_or(Error::InvalidSection(id))?;
+
+    let mut section = UsSection {
+        section_id: id,
+        version,
+        ..UsSection::default()
+    };
+    for field in fields {
+        decode_field(&mut bits, field, &mut section).ok_or(Error::InvalidSection(id))?;
+    }
+
+    if has_gpc {
+        for subsection in subsections {
+            let bytes = decode_base64url(subsection).ok_or(Error::Base64)?;
+            let mut bits = Bits::new(&bytes);
+            if bits.read(2) == Some(1) {
+                section.gpc = Some(bits.read_bool().ok_or(Error::InvalidSection(id))?);
+            }
+        }
+    }
+    Ok(section)
+}
+
+fn decode_field(bits: &mut Bits, field: Field, section: &mut UsSection) -> Option<()> {
+    match field {
+        Field::SensitiveDataProcessing(n) => {
+            section.sensitive_data_processing = (0..n).map(|_| bits.read(2).map(|v| v as u8)).collect::<Option<_>>()?
+        }
+        Field::KnownChildSensitiveDataConsents(n) => {
+            section.known_child_sensitive_data_consents =
+                (0..n).map(|_| bits.read(2).map(|v| v as u8)).collect::<Option<_>>()?
+        }
+        _ => {
+            let signal = match bits.read(2)? {
+                0 => Signal::NotApplicable,
+                1 => Signal::Yes,
+                2 => Signal::No,
+                _ => return None,
+            };
+            let target = match field {
+                Field::SharingNotice => &mut section.sharing_notice,
+                Field::SaleOptOutNotice => &mut section.sale_opt_out_notice,
+                Field::SharingOptOutNotice => &mut section.sharing_opt_out_notice,
+                Field::TargetedAdvertisingOptOutNotice => &mut section.targeted_advertising_opt_out_notice,
+                Field::SensitiveDataProcessingOptOutNotice => &mut section.sensitive_data_processing_opt_out_notice,
+                Field::SensitiveDataLimitUseNotice => &mut section.sensitive_data_limit_use_notice,
+                Field::SaleOptOut => &mut section.sale_opt_out,
+                Field::SharingOptOut => &mut section.sharing_opt_out,
+                Field::TargetedAdvertisingOptOut => &mut section.targeted_advertising_opt_out,
+                Field::PersonalDataConsents => &mut section.personal_data_consents,
+                Field::MspaCoveredTransaction => &mut section.mspa_covered_transaction,
+                Field::MspaOptOutOptionMode => &mut section.mspa_opt_out_option_mode,
+                Field::MspaServiceProviderMode => &mut section.mspa_service_provider_mode,
+                Field::SensitiveDataProcessing(_) | Field::KnownChildSensitiveDataConsents(_) => unreachable!(),
+            };
+            *target = Some(signal);
+        }
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::bits::BitWriter;
+    use super::*;
+
+    #[test]
+    fn header() {
+        let mut w = BitWriter::default();
+        // Sections 2, 6 and 7 to 9.
+        w.write(6, 3)
+            .write(6, 1)
+            .write(12, 3)
+            .write(1, 0)
+            .write_fibonacci(2)
+            .write(1, 0)
+            .write_fibonacci(4)
+            .write(1, 1)
+            .write_fibonacci(1)
+            .write_fibonacci(2);
+        let header = w.to_base64url();
+        let s = format!("{}~~1YN-~~~", header);
+
+        match s.parse::<Gpp>() {
+            Err(Error::Tcf(_)) => (),
+            r => panic!("{:?}", r),
+        }
+        match format!("{}~1YN-", header).parse::<Gpp>() {
+            Err(Error::SectionCount { expected: 5, found: 1 }) => (),
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn us_privacy_section() {
+        let gpp: Gpp = "DBABTA~1YY-".parse().unwrap();
+
+        assert_eq!(vec![US_PRIVACY], gpp.section_ids());
+        assert!(gpp.us_privacy().unwrap().opted_out());
+    }
+
+    #[test]
+    fn us_national_section() {
+        let gpp: Gpp = "DBABLA~BVQqAAAAAgA.QA".parse().unwrap();
+        let us = gpp.us_sections().next().unwrap();
+
+        assert_eq!(US_NATIONAL, us.section_id);
+        assert_eq!(1, us.version);
+        assert_eq!(Some(Signal::Yes), us.sale_opt_out_notice);
+        assert_eq!(Some(Signal::No), us.sale_opt_out);
+        assert_eq!(12, us.sensitive_data_processing.len());
+        assert_eq!(Some(false), us.gpc);
+        assert!(!us.opted_out_of_sale());
+    }
+
+    #[test]
+    fn us_california_section() {
+        let mut w = BitWriter::default();
+        w.write(6, 1).write(2, 1).write(2, 1).write(2, 0).write(2, 1).write(2, 2);
+        for _ in 0..9 {
+            w.write(2, 0);
+        }
+        w.write(2, 0).write(2, 0).write(2, 0).write(2, 1).write(2, 2).write(2, 2);
+        let mut gpc = BitWriter::default();
+        gpc.write(2, 1).write(1, 1);
+
+        let section = decode_us(US_CALIFORNIA, &format!("{}.{}", w.to_base64url(), gpc.to_base64url())).unwrap();
+
+        assert!(section.opted_out_of_sale());
+        assert!(!section.opted_out_of_sharing());
+        assert_eq!(None, section.sharing_notice);
+        assert_eq!(Some(Signal::Yes), section.mspa_covered_transaction);
+        assert_eq!(Some(true), section.gpc);
+    }
+
+    #[test]
+    fn truncated_us_section() {
+        assert_eq!(Err(Error::InvalidSection(US_VIRGINIA)), decode_us(US_VIRGINIA, "BV"));
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Decoding of the privacy strings that travel in a bid request's `ext`
+//! objects, a summary of what they signal, and the data minimisation they
+//! call for (`enforce`).
+
+use std::error;
+use std::fmt::{self, Display};
+
+use v2_5::BidRequest;
+
+mod bits;
+pub mod enforce;
+pub mod gpp;
+pub mod tcf;
+pub mod us_privacy;
+
+pub use self::enforce::{enforce, Policy, Reason, Report, Scrub};
+pub use self::gpp::Gpp;
+pub use self::tcf::TcString;
+pub use self::us_privacy::UsPrivacy;
+
+// A privacy string that was sent but couldn't be decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    Tcf(tcf::Error),
+    UsPrivacy(us_privacy::Error),
+    Gpp(gpp::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Tcf(ref e) => Display::fmt(e, formatter),
+            Error::UsPrivacy(ref e) => Display::fmt(e, formatter),
+            Error::Gpp(ref e) => Display::fmt(e, formatter),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Tcf(ref e) => Some(e),
+            Error::UsPrivacy(ref e) => Some(e),
+            Error::Gpp(ref e) => Some(e),
+        }
+    }
+}
+
+// Every privacy signal of a request, decoded.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PrivacySignals {
+    pub coppa: bool,
+    // regs.ext.gdpr; `None` if not sent.
+    pub gdpr: Option<bool>,
+    // user.ext.consent, or else the GPP TCF EU v2 section.
+    pub tcf: Option<TcString>,
+    // regs.ext.us_privacy.
+    pub us_privacy: Option<UsPrivacy>,
+    // regs.ext.gpp.
+    pub gpp: Option<Gpp>,
+    // regs.ext.gpp_sid: the GPP sections that apply to this request. Left
+    // empty, so that every section applies, if it is malformed.
+    pub gpp_sid: Vec<u16>,
+    // Strings that were sent but couldn't be decoded.
+    pub errors: Vec<Error>,
+}
+
+impl PrivacySignals {
+    pub fn from_request<E>(request: &BidRequest<E>) -> PrivacySignals {
+        let mut signals = PrivacySignals::default();
+
+        if let Some(ref regs) = request.regulations {
+            signals.coppa = regs.coppa;
+            signals.gdpr = regs.gdpr();
+            match regs.gpp_sid() {
+                Some(Ok(sid)) => signals.gpp_sid = sid,
+                Some(Err(e)) => signals.errors.push(Error::Gpp(e)),
+                None => (),
+            }
+            match regs.us_privacy() {
+                Some(Ok(usp)) => signals.us_privacy = Some(usp),
+                Some(Err(e)) => signals.errors.push(Error::UsPrivacy(e)),
+                None => (),
+            }
+            match regs.gpp() {
+                Some(Ok(gpp)) => signals.gpp = Some(gpp),
+                Some(Err(e)) => signals.errors.push(Error::Gpp(e)),
+                None => (),
+            }
+        }
+
+        match request.user.as_ref().and_then(|u| u.consent()) {
+            Some(Ok(tc)) => signals.tcf = Some(tc),
+            Some(Err(e)) => signals.errors.push(Error::Tcf(e)),
+            None => (),
+        }
+        if signals.tcf.is_none() {
+            let tcf = signals.applicable_sections().find_map(|s| match *s {
+                gpp::Section::TcfEuV2(ref tc) => Some((**tc).clone()),
+                _ => None,
+            });
+            signals.tcf = tcf;
+        }
+
+        signals
+    }
+
+    // The GPP sections listed in gpp_sid, or all of them if gpp_sid wasn't
+    // sent.
+    pub fn applicable_sections(&self) -> impl Iterator<Item = &gpp::Section> {
+        let sid = &self.gpp_sid;
+        self.gpp
+            .iter()
+            .flat_map(|g| g.sections.iter())
+            .filter(move |s| sid.is_empty() || sid.contains(&s.id()))
+    }
+
+    // The user opted out of the sale of their personal information, through
+    // the US Privacy string, an applicable GPP section or Global Privacy
+    // Control.
+    pub fn sale_opt_out(&self) -> bool {
+        self.us_privacy.is_some_and(|usp| usp.opted_out())
+            || self.applicable_sections().any(|s| match *s {
+                gpp::Section::UsPrivacy(ref usp) => usp.opted_out(),
+                gpp::Section::Us(ref us) => us.opted_out_of_sale() || us.gpc == Some(true),
+                _ => false,
+            })
+    }
+
+    // The user opted out of sharing for targeted advertising, through an
+    // applicable GPP section or Global Privacy Control.
+    pub fn sharing_opt_out(&self) -> bool {
+        self.applicable_sections().any(|s| match *s {
+            gpp::Section::Us(ref us) => us.opted_out_of_sharing() || us.gpc == Some(true),
+            _ => false,
+        })
+    }
+
+    pub fn opted_out(&self) -> bool {
+        self.sale_opt_out() || self.sharing_opt_out()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    fn request(regs: &str) -> BidRequest {
+        serde_json::from_str(&format!(r#"{{"id": "1", "imp": [], "at": 1, "regs": {}}}"#, regs)).unwrap()
+    }
+
+    #[test]
+    fn us_privacy_opt_out() {
+        let signals = PrivacySignals::from_request(&request(
+            r#"{"coppa": 1, "ext": {"gdpr": 0, "us_privacy": "1YYN"}}"#,
+        ));
+
+        assert!(signals.coppa);
+        assert_eq!(Some(false), signals.gdpr);
+        assert!(signals.sale_opt_out());
+        assert!(!signals.sharing_opt_out());
+    }
+
+    #[test]
+    fn gpp_sid_selects_sections() {
+        let signals = PrivacySignals::from_request(&request(
+            r#"{"ext": {"gpp": "DBABTA~1YY-", "gpp_sid": [7]}}"#,
+        ));
+        assert!(!signals.opted_out());
+
+        let signals = PrivacySignals::from_request(&request(
+            r#"{"ext": {"gpp": "DBABTA~1YY-", "gpp_sid": [6]}}"#,
+        ));
+        assert!(signals.opted_out());
+
+        let signals = PrivacySignals::from_request(&request(
+            r#"{"ext": {"gpp": "DBABTA~1YY-", "gpp_sid": [65543]}}"#,
+        ));
+        assert_eq!(1, signals.errors.len());
+        assert!(signals.opted_out());
+    }
+
+    #[test]
+    fn malformed_strings() {
+        let signals = PrivacySignals::from_request(&request(r#"{"ext": {"us_privacy": "yes", "gpp": "DBABTA"}}"#));
+
+        assert_eq!(2, signals.errors.len());
+        assert!(!signals.opted_out());
+    }
+}
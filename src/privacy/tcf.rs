@@ -0,0 +1,383 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! IAB Europe Transparency & Consent Framework v2 ("TC string") decoding.
+
+use std::collections::BTreeSet;
+use std::error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use super::bits::{decode_base64url, Bits};
+
+pub use super::bits::IdSet;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    // A segment isn't valid base64url.
+    Base64,
+    // The core segment isn't TCF v2.
+    UnsupportedVersion(u8),
+    // A segment ends before its last field, or a vendor range in it is
+    // reversed or past the maximum vendor ID.
+    Truncated,
+    // An optional segment has an unknown type.
+    UnknownSegment(u8),
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Base64 => formatter.write_str("TC string is not base64url"),
+            Error::UnsupportedVersion(v) => write!(formatter, "unsupported TC string version {}", v),
+            Error::Truncated => formatter.write_str("TC string is truncated"),
+            Error::UnknownSegment(t) => write!(formatter, "unknown TC string segment type {}", t),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestrictionType {
+    // The vendor may not process data for the purpose at all.
+    NotAllowed,
+    // The vendor must have consent, even if it declared legitimate
+    // interest.
+    RequireConsent,
+    // The vendor must rely on legitimate interest, even if it declared
+    // consent.
+    RequireLegitimateInterest,
+    // Reserved for future use.
+    Undefined,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublisherRestriction {
+    pub purpose_id: u8,
+    pub restriction_type: RestrictionType,
+    pub vendors: IdSet,
+}
+
+// The publisher's own transparency and consent signals.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PublisherTc {
+    pub purposes_consent: BTreeSet<u16>,
+    pub purposes_legitimate_interest: BTreeSet<u16>,
+    pub custom_purposes_consent: BTreeSet<u16>,
+    pub custom_purposes_legitimate_interest: BTreeSet<u16>,
+}
+
+// A decoded TC string. Purpose and special feature IDs are kept as the
+// sets of IDs whose bit is set, vendor IDs as ranges.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TcString {
+    pub version: u8,
+    // Deciseconds since the Unix epoch.
+    pub created: u64,
+    pub last_updated: u64,
+    pub cmp_id: u16,
+    pub cmp_version: u16,
+    pub consent_screen: u8,
+    // ISO 639-1 language of the consent screen, e.g. "EN".
+    pub consent_language: String,
+    pub vendor_list_version: u16,
+    pub tcf_policy_version: u8,
+    pub is_service_specific: bool,
+    pub use_non_standard_texts: bool,
+    pub special_feature_opt_ins: BTreeSet<u16>,
+    pub purposes_consent: BTreeSet<u16>,
+    pub purposes_legitimate_interest: BTreeSet<u16>,
+    // Purpose 1 was not disclosed, for publishers in countries where it
+    // isn't required.
+    pub purpose_one_treatment: bool,
+    // ISO 3166-1 alpha-2 country of the publisher.
+    pub publisher_cc: String,
+    pub vendor_consents: IdSet,
+    pub vendor_legitimate_interests: IdSet,
+    pub publisher_restrictions: Vec<PublisherRestriction>,
+    // Vendors disclosed to the user by the CMP, if the segment is present.
+    pub disclosed_vendors: Option<IdSet>,
+    // Vendors the publisher allows, if the segment is present.
+    pub allowed_vendors: Option<IdSet>,
+    pub publisher_tc: Option<PublisherTc>,
+}
+
+impl FromStr for TcString {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<TcString, Error> {
+        let mut segments = s.trim().split('.');
+        let core = decode_base64url(segments.next().unwrap_or("")).ok_or(Error::Base64)?;
+        let mut tc = decode_core(&mut Bits::new(&core))?;
+
+        for segment in segments {
+            let bytes = decode_base64url(segment).ok_or(Error::Base64)?;
+            let mut bits = Bits::new(&bytes);
+            match bits.read(3).ok_or(Error::Truncated)? {
+                1 => tc.disclosed_vendors = Some(bits.read_vendors().ok_or(Error::Truncated)?),
+                2 => tc.allowed_vendors = Some(bits.read_vendors().ok_or(Error::Truncated)?),
+                3 => tc.publisher_tc = Some(decode_publisher_tc(&mut bits).ok_or(Error::Truncated)?),
+                t => return Err(Error::UnknownSegment(t as u8)),
+            }
+        }
+        Ok(tc)
+    }
+}
+
+impl TcString {
+    // Whether the vendor may process personal data for all of `purposes` on
+    // the basis of consent: the vendor was disclosed to the user and is
+    // allowed by the publisher (when those segments are present), the user
+    // consented to the vendor and to each purpose, and no publisher
+    // restriction rules consent out.
+    pub fn vendor_allowed(&self, vendor_id: u16, purposes: &[u16]) -> bool {
+        self.vendor_listed(vendor_id)
+            && self.vendor_consents.contains(vendor_id)
+            && purposes.iter().all(|p| {
+                self.purposes_consent.contains(p)
+                    && !self.restricted(vendor_id, *p, RestrictionType::NotAllowed)
+                    && !self.restricted(vendor_id, *p, RestrictionType::RequireLegitimateInterest)
+            })
+    }
+
+    // Same as `vendor_allowed`, for a vendor relying on legitimate
+    // interest. Purpose 1 always requires consent.
+    pub fn vendor_allowed_by_legitimate_interest(&self, vendor_id: u16, purposes: &[u16]) -> bool {
+        self.vendor_listed(vendor_id)
+            && self.vendor_legitimate_interests.contains(vendor_id)
+            && purposes.iter().all(|p| {
+                *p != 1
+                    && self.purposes_legitimate_interest.contains(p)
+                    && !self.restricted(vendor_id, *p, RestrictionType::NotAllowed)
+                    && !self.restricted(vendor_id, *p, RestrictionType::RequireConsent)
+            })
+    }
+
+    pub fn special_feature_opted_in(&self, feature_id: u16) -> bool {
+        self.special_feature_opt_ins.contains(&feature_id)
+    }
+
+    fn vendor_listed(&self, vendor_id: u16) -> bool {
+        self.disclosed_vendors.as_ref().is_none_or(|v| v.contains(vendor_id))
+            && self.allowed_vendors.as_ref().is_none_or(|v| v.contains(vendor_id))
+    }
+
+    fn restricted(&self, vendor_id: u16, purpose_id: u16, restriction_type: RestrictionType) -> bool {
+        self.publisher_restrictions.iter().any(|r| {
+            u16::from(r.purpose_id) == purpose_id
+                && r.restriction_type == restriction_type
+                && r.vendors.contains(vendor_id)
+        })
+    }
+}
+
+fn decode_core(bits: &mut Bits) -> Result<TcString, Error> {
+    let version = bits.read(6).ok_or(Error::Truncated)? as u8;
+    if version != 2 {
+        return Err(Error::UnsupportedVersion(version));
+    }
+    decode_core_v2(bits, version).ok_or(Error::Truncated)
+}
+
+fn decode_core_v2(bits: &mut Bits, version: u8) -> Option<TcString> {
+    let mut tc = TcString {
+        version,
+        created: bits.read(36)?,
+        last_updated: bits.read(36)?,
+        cmp_id: bits.read(12)? as u16,
+        cmp_version: bits.read(12)? as u16,
+        consent_screen: bits.read(6)? as u8,
+        consent_language: bits.read_letters(2)?,
+        vendor_list_version: bits.read(12)? as u16,
+        tcf_policy_version: bits.read(6)? as u8,
+        is_service_specific: bits.read_bool()?,
+        use_non_standard_texts: bits.read_bool()?,
+        special_feature_opt_ins: bits.read_bitfield(12)?,
+        purposes_consent: bits.read_bitfield(24)?,
+        purposes_legitimate_interest: bits.read_bitfield(24)?,
+        purpose_one_treatment: bits.read_bool()?,
+        publisher_cc: bits.read_letters(2)?,
+        vendor_consents: bits.read_vendors()?,
+        vendor_legitimate_interests: bits.read_vendors()?,
+        publisher_restrictions: vec![],
+        disclosed_vendors: None,
+        allowed_vendors: None,
+        publisher_tc: None,
+    };
+
+    for _ in 0..bits.read(12)? {
+        let purpose_id = bits.read(6)? as u8;
+        let restriction_type = match bits.read(2)? {
+            0 => RestrictionType::NotAllowed,
+            1 => RestrictionType::RequireConsent,
+            2 => RestrictionType::RequireLegitimateInterest,
+            _ => RestrictionType::Undefined,
+        };
+        tc.publisher_restrictions.push(PublisherRestriction {
+            purpose_id,
+            restriction_type,
+            vendors: bits.read_ranges(u16::MAX)?,
+        });
+    }
+    Some(tc)
+}
+
+fn decode_publisher_tc(bits: &mut Bits) -> Option<PublisherTc> {
+    let purposes_consent = bits.read_bitfield(24)?;
+    let purposes_legitimate_interest = bits.read_bitfield(24)?;
+    let custom = bits.read(6)? as usize;
+    Some(PublisherTc {
+        purposes_consent,
+        purposes_legitimate_interest,
+        custom_purposes_consent: bits.read_bitfield(custom)?,
+        custom_purposes_legitimate_interest: bits.read_bitfield(custom)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::bits::BitWriter;
+    use super::*;
+
+    fn core() -> BitWriter {
+        let mut w = BitWriter::default();
+        w.write(6, 2)
+            .write(36, 15_100_000_000)
+            .write(36, 15_100_000_000)
+            .write(12, 7)
+            .write(12, 1)
+            .write(6, 3)
+            .write(6, 4) // E
+            .write(6, 13) // N
+            .write(12, 15)
+            .write(6, 2)
+            .write(1, 0)
+            .write(1, 0)
+            .write_ids(12, &[1])
+            .write_ids(24, &[1, 2, 3, 4, 7])
+            .write_ids(24, &[2, 7, 10])
+            .write(1, 0)
+            .write(6, 3) // D
+            .write(6, 4); // E
+        // Vendor consents as a bitfield: 2 and 6.
+        w.write(16, 6).write(1, 0).write_ids(6, &[2, 6]);
+        // Vendor legitimate interests as ranges: 6, and 10 to 12.
+        w.write(16, 12)
+            .write(1, 1)
+            .write(12, 2)
+            .write(1, 0)
+            .write(16, 6)
+            .write(1, 1)
+            .write(16, 10)
+            .write(16, 12);
+        // One restriction: purpose 7 needs legitimate interest for vendor 6.
+        w.write(12, 1).write(6, 7).write(2, 2).write(12, 1).write(1, 0).write(16, 6);
+        w
+    }
+
+    #[test]
+    fn decode_core_segment() {
+        let tc: TcString = core().to_base64url().parse().unwrap();
+
+        assert_eq!(2, tc.version);
+        assert_eq!(7, tc.cmp_id);
+        assert_eq!("EN", tc.consent_language);
+        assert_eq!("DE", tc.publisher_cc);
+        assert!(tc.special_feature_opted_in(1));
+        assert_eq!(vec![2, 6], tc.vendor_consents.iter().collect::<Vec<_>>());
+        assert_eq!(
+            vec![6, 10, 11, 12],
+            tc.vendor_legitimate_interests.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(RestrictionType::RequireLegitimateInterest, tc.publisher_restrictions[0].restriction_type);
+        assert_eq!(None, tc.disclosed_vendors);
+    }
+
+    #[test]
+    fn vendor_allowed() {
+        let tc: TcString = core().to_base64url().parse().unwrap();
+
+        assert!(tc.vendor_allowed(2, &[1, 2, 7]));
+        assert!(tc.vendor_allowed(6, &[1, 2]));
+        // Purpose 5 has no consent.
+        assert!(!tc.vendor_allowed(2, &[1, 5]));
+        // Vendor 3 has no consent.
+        assert!(!tc.vendor_allowed(3, &[1]));
+        // The publisher requires legitimate interest for vendor 6 and purpose 7.
+        assert!(!tc.vendor_allowed(6, &[7]));
+        assert!(tc.vendor_allowed_by_legitimate_interest(6, &[7]));
+        assert!(!tc.vendor_allowed_by_legitimate_interest(6, &[1]));
+    }
+
+    #[test]
+    fn decode_optional_segments() {
+        let mut disclosed = BitWriter::default();
+        disclosed.write(3, 1).write(16, 3).write(1, 0).write_ids(3, &[1, 3]);
+        let mut publisher = BitWriter::default();
+        publisher
+            .write(3, 3)
+            .write_ids(24, &[1])
+            .write_ids(24, &[])
+            .write(6, 2)
+            .write_ids(2, &[2])
+            .write_ids(2, &[]);
+        let s = format!(
+            "{}.{}.{}",
+            core().to_base64url(),
+            disclosed.to_base64url(),
+            publisher.to_base64url()
+        );
+
+        let tc: TcString = s.parse().unwrap();
+
+        assert_eq!(vec![1, 3], tc.disclosed_vendors.as_ref().unwrap().iter().collect::<Vec<_>>());
+        // Vendor 2 has consent but wasn't disclosed to the user.
+        assert!(!tc.vendor_allowed(2, &[1]));
+        let publisher_tc = tc.publisher_tc.unwrap();
+        assert!(publisher_tc.purposes_consent.contains(&1));
+        assert!(publisher_tc.custom_purposes_consent.contains(&2));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Err(Error::Base64), "CO*".parse::<TcString>());
+        // "BO..." is a TCF v1 consent string.
+        assert_eq!(
+            Err(Error::UnsupportedVersion(1)),
+            "BOEFEAyOEFEAyAHABDENAI4AAAB9vABAASA".parse::<TcString>()
+        );
+        assert_eq!(Err(Error::Truncated), "CPXxRfAPXxRfA".parse::<TcString>());
+    }
+
+    #[test]
+    fn vendor_ranges() {
+        let segment = |ranges: &[(u64, u64)]| {
+            let mut w = BitWriter::default();
+            w.write(3, 2).write(16, 100).write(1, 1).write(12, ranges.len() as u64);
+            for &(start, end) in ranges {
+                w.write(1, 1).write(16, start).write(16, end);
+            }
+            format!("{}.{}", core().to_base64url(), w.to_base64url())
+        };
+
+        let tc: TcString = segment(&[(50, 100), (2, 10), (8, 12)]).parse().unwrap();
+        let allowed = tc.allowed_vendors.as_ref().unwrap();
+        assert!(allowed.contains(2) && allowed.contains(12) && allowed.contains(100));
+        assert!(!allowed.contains(1) && !allowed.contains(13) && !allowed.contains(101));
+        assert_eq!(62, allowed.iter().count());
+        assert!(tc.vendor_allowed(6, &[1]));
+
+        // Vendor 6 has consent but the publisher doesn't allow it.
+        let tc: TcString = segment(&[(50, 100)]).parse().unwrap();
+        assert!(!tc.vendor_allowed(6, &[1]));
+
+        assert_eq!(Err(Error::Truncated), segment(&[(10, 2)]).parse::<TcString>());
+        assert_eq!(Err(Error::Truncated), segment(&[(1, 65535)]).parse::<TcString>());
+    }
+}
@@ -0,0 +1,138 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The IAB CCPA Compliance Framework's US Privacy string, e.g. "1YNN".
+
+use std::error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+// A three-state field, shared with the GPP US sections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    NotApplicable,
+    Yes,
+    No,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    // The string isn't 4 characters long.
+    Length(usize),
+    UnsupportedVersion(char),
+    // A field isn't 'Y', 'N' or '-'.
+    InvalidField(usize, char),
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Length(n) => write!(formatter, "US Privacy string has {} characters, expected 4", n),
+            Error::UnsupportedVersion(c) => write!(formatter, "unsupported US Privacy version {:?}", c),
+            Error::InvalidField(i, c) => write!(formatter, "invalid US Privacy field {}: {:?}", i, c),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UsPrivacy {
+    pub version: u8,
+    // Explicit notice and opportunity to opt out was given.
+    pub notice: Signal,
+    // The user opted out of the sale of their personal information.
+    pub opt_out_sale: Signal,
+    // The publisher is a signatory of the IAB Limited Service Provider
+    // Agreement.
+    pub lspa_covered: Signal,
+}
+
+impl UsPrivacy {
+    pub fn opted_out(&self) -> bool {
+        self.opt_out_sale == Signal::Yes
+    }
+}
+
+impl Signal {
+    fn from_char(c: char) -> Option<Signal> {
+        match c {
+            'Y' | 'y' => Some(Signal::Yes),
+            'N' | 'n' => Some(Signal::No),
+            '-' => Some(Signal::NotApplicable),
+            _ => None,
+        }
+    }
+
+    fn to_char(self) -> char {
+        match self {
+            Signal::Yes => 'Y',
+            Signal::No => 'N',
+            Signal::NotApplicable => '-',
+        }
+    }
+}
+
+impl FromStr for UsPrivacy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<UsPrivacy, Error> {
+        let chars: Vec<char> = s.trim().chars().collect();
+        if chars.len() != 4 {
+            return Err(Error::Length(chars.len()));
+        }
+        if chars[0] != '1' {
+            return Err(Error::UnsupportedVersion(chars[0]));
+        }
+        let field = |i: usize| Signal::from_char(chars[i]).ok_or(Error::InvalidField(i, chars[i]));
+
+        Ok(UsPrivacy {
+            version: 1,
+            notice: field(1)?,
+            opt_out_sale: field(2)?,
+            lspa_covered: field(3)?,
+        })
+    }
+}
+
+impl Display for UsPrivacy {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{}{}{}{}",
+            self.version,
+            self.notice.to_char(),
+            self.opt_out_sale.to_char(),
+            self.lspa_covered.to_char()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let usp: UsPrivacy = "1YYN".parse().unwrap();
+
+        assert_eq!(Signal::Yes, usp.notice);
+        assert!(usp.opted_out());
+        assert_eq!(Signal::No, usp.lspa_covered);
+        assert_eq!("1YYN", usp.to_string());
+
+        assert!(!"1---".parse::<UsPrivacy>().unwrap().opted_out());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Err(Error::Length(3)), "1YN".parse::<UsPrivacy>());
+        assert_eq!(Err(Error::UnsupportedVersion('2')), "2YNN".parse::<UsPrivacy>());
+        assert_eq!(Err(Error::InvalidField(2, 'X')), "1YXN".parse::<UsPrivacy>());
+    }
+}
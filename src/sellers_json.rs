@@ -0,0 +1,350 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! sellers.json (IAB Tech Lab, version 1.0) documents, and verification of a
+//! `SupplyChain` against the sellers.json of each advertising system it
+//! names.
+//!
+//! Documents are looked up through a caller-supplied function keyed by the
+//! node's `asi`, so they can come from a local cache, a database or a test
+//! fixture.
+
+use serde;
+
+use parse;
+use serde_utils;
+use v2_5::SupplyChain;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SellersJson {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact_email: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact_address: Option<String>,
+
+    // Version of the spec in use, e.g. "1.0".
+    #[serde(default)]
+    pub version: String,
+
+    // Business identifiers of the advertising system itself, e.g. its
+    // TAG-ID.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub identifiers: Vec<Identifier>,
+
+    #[serde(default)]
+    pub sellers: Vec<Seller>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<serde_utils::Ext>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Identifier {
+    // e.g. "TAG-ID" or "DUNS".
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Seller {
+    // The seller's account ID within the advertising system; the same
+    // value as `SupplyChainNode.sid` and the ads.txt account ID. Some
+    // systems publish it as a number.
+    #[serde(deserialize_with = "serde_utils::anything_to_string")]
+    pub seller_id: String,
+
+    // Whether the seller's identity is withheld; `name` and `domain` are
+    // then omitted.
+    #[serde(
+        default = "serde_utils::default_false",
+        skip_serializing_if = "serde_utils::is_false",
+        serialize_with = "serde_utils::bool_to_u8",
+        deserialize_with = "serde_utils::u8_to_bool"
+    )]
+    pub is_confidential: bool,
+
+    pub seller_type: SellerType,
+
+    // Whether the advertising system passes the seller's inventory
+    // through without taking part in the transaction.
+    #[serde(
+        default = "serde_utils::default_false",
+        skip_serializing_if = "serde_utils::is_false",
+        serialize_with = "serde_utils::bool_to_u8",
+        deserialize_with = "serde_utils::u8_to_bool"
+    )]
+    pub is_passthrough: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    // Business domain of the seller.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<serde_utils::Ext>,
+}
+
+// Read ignoring case. Real files are messy, so a type the spec doesn't
+// define is kept as it is rather than failing the whole document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SellerType {
+    // The seller owns the inventory.
+    Publisher,
+    // The seller resells inventory owned by others.
+    Intermediary,
+    // The seller does both, through the same account.
+    Both,
+    // Anything else; fits neither position.
+    Other(String),
+}
+
+// The outcome of verifying one node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeVerification {
+    pub asi: String,
+    pub sid: String,
+
+    // Whether a sellers.json was supplied for `asi`.
+    pub document_found: bool,
+
+    // The seller listed under `sid`, if any.
+    pub seller: Option<Seller>,
+
+    // Whether the seller's type fits the node's position: the first node
+    // of a complete chain owns the inventory, the others resell it. `None`
+    // if the seller isn't listed or the position says nothing, i.e. the
+    // first node of an incomplete chain.
+    pub type_matches: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Verification {
+    // The chain's own `complete` flag.
+    pub complete: bool,
+    pub nodes: Vec<NodeVerification>,
+}
+
+impl SellersJson {
+    pub fn parse(bytes: &[u8]) -> Result<SellersJson, parse::Error> {
+        parse::from_slice(bytes)
+    }
+
+    pub fn seller(&self, seller_id: &str) -> Option<&Seller> {
+        self.sellers.iter().find(|s| s.seller_id == seller_id)
+    }
+}
+
+impl SellerType {
+    pub fn is_publisher(&self) -> bool {
+        match *self {
+            SellerType::Publisher | SellerType::Both => true,
+            SellerType::Intermediary | SellerType::Other(_) => false,
+        }
+    }
+
+    pub fn is_intermediary(&self) -> bool {
+        match *self {
+            SellerType::Intermediary | SellerType::Both => true,
+            SellerType::Publisher | SellerType::Other(_) => false,
+        }
+    }
+}
+
+impl serde::Serialize for SellerType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(match *self {
+            SellerType::Publisher => "PUBLISHER",
+            SellerType::Intermediary => "INTERMEDIARY",
+            SellerType::Both => "BOTH",
+            SellerType::Other(ref s) => s,
+        })
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for SellerType {
+    fn deserialize<D>(deserializer: D) -> Result<SellerType, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s: String = serde::Deserialize::deserialize(deserializer)?;
+        Ok(match s.to_ascii_uppercase().as_str() {
+            "PUBLISHER" => SellerType::Publisher,
+            "INTERMEDIARY" => SellerType::Intermediary,
+            "BOTH" => SellerType::Both,
+            _ => SellerType::Other(s),
+        })
+    }
+}
+
+impl NodeVerification {
+    // The seller is listed and its type fits the node's position.
+    pub fn is_verified(&self) -> bool {
+        self.seller.is_some() && self.type_matches != Some(false)
+    }
+}
+
+impl Verification {
+    // The chain is complete and every node is verified.
+    pub fn is_verified(&self) -> bool {
+        self.complete && !self.nodes.is_empty() && self.nodes.iter().all(NodeVerification::is_verified)
+    }
+}
+
+// Verifies every node of `schain` against the sellers.json returned by
+// `lookup` for the node's `asi` (compared lowercased).
+pub fn verify<'a, F>(schain: &SupplyChain, lookup: F) -> Verification
+where
+    F: Fn(&str) -> Option<&'a SellersJson>,
+{
+    let nodes = schain
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| {
+            let document = lookup(&node.asi.to_ascii_lowercase());
+            let seller = document.and_then(|d| d.seller(&node.sid)).cloned();
+            let type_matches = seller.as_ref().and_then(|s| match i {
+                0 if schain.complete => Some(s.seller_type.is_publisher()),
+                0 => None,
+                _ => Some(s.seller_type.is_intermediary()),
+            });
+
+            NodeVerification {
+                asi: node.asi.clone(),
+                sid: node.sid.clone(),
+                document_found: document.is_some(),
+                seller,
+                type_matches,
+            }
+        })
+        .collect();
+
+    Verification {
+        complete: schain.complete,
+        nodes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+    use std::collections::HashMap;
+    use v2_5::SupplyChainNode;
+
+    const SSP: &str = r#"{
+        "contact_email": "adops@ssp.com",
+        "version": "1.0",
+        "identifiers": [{"name": "TAG-ID", "value": "28cb65e5bbc0bd5f"}],
+        "sellers": [
+            {"seller_id": "1942009976", "name": "Publisher", "domain": "publisher.com", "seller_type": "PUBLISHER"},
+            {"seller_id": 42, "is_confidential": 1, "seller_type": "intermediary"}
+        ]
+    }"#;
+
+    const EXCHANGE: &str = r#"{
+        "version": "1.0",
+        "sellers": [
+            {"seller_id": "abc", "name": "SSP", "domain": "ssp.com", "seller_type": "BOTH"}
+        ]
+    }"#;
+
+    fn documents() -> HashMap<String, SellersJson> {
+        let mut documents = HashMap::new();
+        documents.insert("ssp.com".to_string(), SellersJson::parse(SSP.as_bytes()).unwrap());
+        documents.insert("exchange.com".to_string(), SellersJson::parse(EXCHANGE.as_bytes()).unwrap());
+        documents
+    }
+
+    fn schain(complete: bool, nodes: &[(&str, &str)]) -> SupplyChain {
+        let mut schain = SupplyChain::new(complete);
+        for &(asi, sid) in nodes {
+            schain
+                .append_node(SupplyChainNode::new(asi.to_string(), sid.to_string()))
+                .unwrap();
+        }
+        schain
+    }
+
+    #[test]
+    fn parse() {
+        let doc = SellersJson::parse(SSP.as_bytes()).unwrap();
+
+        assert_eq!("TAG-ID", doc.identifiers[0].name);
+        let seller = doc.seller("42").unwrap();
+        assert!(seller.is_confidential);
+        assert_eq!(SellerType::Intermediary, seller.seller_type);
+        assert_eq!(None, seller.name);
+    }
+
+    #[test]
+    fn unknown_seller_type() {
+        let doc = SellersJson::parse(
+            br#"{"sellers": [
+                {"seller_id": "1", "seller_type": "OWNER"},
+                {"seller_id": "2", "seller_type": "Publisher"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let owner = &doc.seller("1").unwrap().seller_type;
+        assert_eq!(SellerType::Other("OWNER".to_string()), *owner);
+        assert!(!owner.is_publisher() && !owner.is_intermediary());
+        assert_eq!(SellerType::Publisher, doc.seller("2").unwrap().seller_type);
+        assert_eq!(r#""OWNER""#, serde_json::to_string(owner).unwrap());
+    }
+
+    #[test]
+    fn verified_chain() {
+        let documents = documents();
+        let v = verify(
+            &schain(true, &[("ssp.com", "1942009976"), ("exchange.com", "abc")]),
+            |asi| documents.get(asi),
+        );
+
+        assert!(v.is_verified());
+        assert_eq!(Some(true), v.nodes[1].type_matches);
+    }
+
+    #[test]
+    fn unverified_chain() {
+        let documents = documents();
+        let v = verify(
+            &schain(
+                true,
+                &[("SSP.com", "42"), ("exchange.com", "missing"), ("unknown.com", "1")],
+            ),
+            |asi| documents.get(asi),
+        );
+
+        assert!(!v.is_verified());
+        // An intermediary can't own the inventory.
+        assert_eq!(Some(false), v.nodes[0].type_matches);
+        assert!(v.nodes[1].document_found);
+        assert_eq!(None, v.nodes[1].seller);
+        assert!(!v.nodes[2].document_found);
+    }
+
+    #[test]
+    fn incomplete_chain() {
+        let documents = documents();
+        let v = verify(&schain(false, &[("ssp.com", "42")]), |asi| documents.get(asi));
+
+        assert!(v.nodes[0].is_verified());
+        assert!(!v.is_verified());
+    }
+}
@@ -12,6 +12,39 @@ use serde::de::Visitor;
 
 pub type Ext = serde_json::map::Map<String, serde_json::value::Value>;
 
+// The newtype struct name that marks values accepted although they aren't
+// part of the spec: unknown categories and captured unknown keys. Formats
+// treat newtype structs as their content, so the mark only shows to a
+// serializer that looks for it, like the one in the strict module.
+pub const OUTSIDE_SPEC: &str = "$openrtb::OutsideSpec";
+
+pub struct OutsideSpec<'a, T: ?Sized + 'a>(pub &'a T);
+
+impl<'a, T> serde::Serialize for OutsideSpec<'a, T>
+where
+    T: ?Sized + serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_newtype_struct(OUTSIDE_SPEC, self.0)
+    }
+}
+
+// With the `unknown-fields` feature every object has an `other` map,
+// flattened into it, that collects the keys the spec doesn't define for it
+// so they survive a round trip. Keys the object does define are always
+// read into their fields, so a 0/1 flag with an invalid value is still
+// read as `None` and not written back.
+#[cfg(feature = "unknown-fields")]
+pub fn serialize_other<S>(other: &Ext, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.collect_map(other.iter().map(|(k, v)| (k, OutsideSpec(v))))
+}
+
 struct BoolVisitor;
 
 impl<'de> Visitor<'de> for BoolVisitor {
@@ -79,7 +112,7 @@ impl<'de> Visitor<'de> for StringVisitor {
     type Value = String;
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-        formatter.write_str("a bool value or integer 0 or 1")
+        formatter.write_str("a string, number or bool value")
     }
 
     fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E>
@@ -174,11 +207,18 @@ where
     serializer.serialize_u8(*x as u8)
 }
 
+// Binary formats aren't guaranteed to be self-describing, so only JSON-like
+// formats get the lenient `deserialize_any` treatment; the rest are asked
+// for the u8 that `bool_to_u8` wrote.
 pub fn u8_to_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    deserializer.deserialize_any(BoolVisitor)
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(BoolVisitor)
+    } else {
+        deserializer.deserialize_u8(BoolVisitor)
+    }
 }
 
 pub fn default_false() -> bool {
@@ -195,17 +235,130 @@ where
 {
     match mx {
         None => serializer.serialize_none(),
-        Some(x) => bool_to_u8(x, serializer),
+        Some(x) => serializer.serialize_some(&(*x as u8)),
+    }
+}
+
+// Reads an optional 0/1 flag. Anything that isn't a valid flag maps to
+// `None`, in every format; the value is consumed rather than rejected so
+// that nothing upstream (e.g. path tracking) sees an error.
+struct MBoolVisitor {
+    // Whether the format describes its values, so any of them can be read.
+    self_describing: bool,
+}
+
+impl<'de> Visitor<'de> for MBoolVisitor {
+    type Value = Option<bool>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an optional bool value or integer 0 or 1")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Some(value))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        match value {
+            0 => Ok(Some(false)),
+            1 => Ok(Some(true)),
+            _ => Ok(None),
+        }
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        match value {
+            0 => Ok(Some(false)),
+            1 => Ok(Some(true)),
+            _ => Ok(None),
+        }
+    }
+
+    fn visit_f64<E>(self, _value: f64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(None)
+    }
+
+    fn visit_str<E>(self, _value: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(None)
+    }
+
+    fn visit_bytes<E>(self, _value: &[u8]) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(None)
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(None)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(None)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        if self.self_describing {
+            deserializer.deserialize_any(self)
+        } else {
+            deserializer.deserialize_u8(self)
+        }
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        while seq.next_element::<serde::de::IgnoredAny>()?.is_some() {}
+        Ok(None)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        while map
+            .next_entry::<serde::de::IgnoredAny, serde::de::IgnoredAny>()?
+            .is_some()
+        {}
+        Ok(None)
     }
 }
 
+// Binary formats aren't guaranteed to be self-describing, so they are
+// asked for the optional u8 that `mbool_to_u8` wrote.
 pub fn u8_to_mbool<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    match deserializer.deserialize_any(BoolVisitor) {
-        Ok(value) => Ok(Some(value)),
-        Err(_) => Ok(None),
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(MBoolVisitor { self_describing: true })
+    } else {
+        deserializer.deserialize_option(MBoolVisitor { self_describing: false })
     }
 }
 
@@ -221,5 +374,62 @@ pub fn anything_to_string<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    deserializer.deserialize_any(StringVisitor)
-}
\ No newline at end of file
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(StringVisitor)
+    } else {
+        deserializer.deserialize_string(StringVisitor)
+    }
+}
+
+// Reads a list of third-party data, such as extended identifiers, leaving
+// out the entries that don't deserialize as `T` instead of failing. Binary
+// formats aren't guaranteed to be self-describing, so they read the list
+// as it is.
+pub fn skip_invalid<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    if deserializer.is_human_readable() {
+        let values: Vec<serde_json::Value> = serde::Deserialize::deserialize(deserializer)?;
+        Ok(values.into_iter().filter_map(|v| T::deserialize(v).ok()).collect())
+    } else {
+        serde::Deserialize::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bincode;
+    use v2_5::{AdPosition, Automotive, Category};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Flags {
+        #[serde(serialize_with = "bool_to_u8", deserialize_with = "u8_to_bool")]
+        flag: bool,
+        #[serde(serialize_with = "mbool_to_u8", deserialize_with = "u8_to_mbool")]
+        set: Option<bool>,
+        #[serde(serialize_with = "mbool_to_u8", deserialize_with = "u8_to_mbool")]
+        unset: Option<bool>,
+        #[serde(deserialize_with = "anything_to_string")]
+        id: String,
+        cat: Vec<Category>,
+        pos: AdPosition,
+    }
+
+    #[test]
+    fn helpers_in_a_non_self_describing_format() {
+        let flags = Flags {
+            flag: true,
+            set: Some(false),
+            unset: None,
+            id: "42".to_string(),
+            cat: vec![Category::Automotive(Automotive::Automotive), Category::Unknown("XYZ1".to_string())],
+            pos: AdPosition::Header,
+        };
+        let bytes = bincode::serialize(&flags).unwrap();
+
+        assert_eq!(flags, bincode::deserialize(&bytes).unwrap());
+    }
+}
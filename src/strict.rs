@@ -0,0 +1,645 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Strict deserialization, for certifying that a partner sends exactly what
+//! the spec describes.
+//!
+//! The regular `Deserialize` impls are lenient: they drop unknown keys,
+//! accept `true`/`false` for 0/1 flags, numbers for string ids and any
+//! string as a category. The functions here deserialize the same way, then
+//! compare what was sent against what was understood and report every
+//! difference with its JSON pointer.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::error;
+use std::fmt::{self, Display};
+
+use serde::de::DeserializeOwned;
+use serde::ser::{self, Serialize};
+use serde_ignored;
+use serde_json::{self, Value};
+
+use serde_utils;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeviationKind {
+    // The key isn't defined by the spec for this object.
+    UnknownField,
+    // `true`/`false` was sent where the spec asks for 0 or 1.
+    BooleanLiteral,
+    // A number was sent where the spec asks for a string.
+    NumberForString,
+    // The category is not in List 5.1.
+    UnknownCategory,
+    // The value couldn't be used and was dropped.
+    Ignored,
+    // The value was read as something other than what was sent.
+    Altered,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deviation {
+    // JSON pointer to the offending value, e.g. "/imp/0/instl".
+    pub path: String,
+    pub kind: DeviationKind,
+    // The value as it was sent.
+    pub value: Value,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    // The input isn't valid JSON or can't be read as the target type even
+    // leniently.
+    Json(serde_json::Error),
+    Deviations(Vec<Deviation>),
+}
+
+impl Display for DeviationKind {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(match *self {
+            DeviationKind::UnknownField => "unknown field",
+            DeviationKind::BooleanLiteral => "boolean literal instead of 0/1",
+            DeviationKind::NumberForString => "number instead of string",
+            DeviationKind::UnknownCategory => "category not in the IAB list",
+            DeviationKind::Ignored => "invalid value ignored",
+            DeviationKind::Altered => "value altered on read",
+        })
+    }
+}
+
+impl Display for Deviation {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}: {} ({})", self.path, self.kind, self.value)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Json(ref e) => Display::fmt(e, formatter),
+            Error::Deviations(ref ds) => {
+                write!(formatter, "{} deviation(s) from spec", ds.len())?;
+                for d in ds {
+                    write!(formatter, "\n{}", d)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Json(ref e) => Some(e),
+            Error::Deviations(_) => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Error {
+        Error::Json(e)
+    }
+}
+
+pub fn from_str<T>(s: &str) -> Result<T, Error>
+where
+    T: DeserializeOwned + Serialize,
+{
+    from_slice(s.as_bytes())
+}
+
+pub fn from_slice<T>(bytes: &[u8]) -> Result<T, Error>
+where
+    T: DeserializeOwned + Serialize,
+{
+    let input: Value = serde_json::from_slice(bytes)?;
+    let (value, deviations) = check(&input)?;
+    if deviations.is_empty() {
+        Ok(value)
+    } else {
+        Err(Error::Deviations(deviations))
+    }
+}
+
+// Deserializes `input` leniently and returns the value together with every
+// deviation from the spec found on the way.
+pub fn check<T>(input: &Value) -> Result<(T, Vec<Deviation>), serde_json::Error>
+where
+    T: DeserializeOwned + Serialize,
+{
+    let mut unknown = vec![];
+    let value: T = serde_ignored::deserialize(input, |path| unknown.push(pointer(&path)))?;
+
+    // `full` is what re-serialization produces; `outside` has the pointers
+    // of the values in it that are marked as outside the spec.
+    let full = serde_json::to_value(&value)?;
+    let outside = RefCell::new(HashSet::new());
+    value.serialize(Marks {
+        path: String::new(),
+        out: &outside,
+    })?;
+
+    let mut deviations = vec![];
+    let mut path = String::new();
+    let outside = outside.into_inner();
+    walk(input, Some(&full), &mut path, &unknown, &outside, &mut deviations);
+
+    Ok((value, deviations))
+}
+
+fn pointer(path: &serde_ignored::Path) -> String {
+    match *path {
+        serde_ignored::Path::Root => String::new(),
+        serde_ignored::Path::Seq { parent, index } => format!("{}/{}", pointer(parent), index),
+        serde_ignored::Path::Map { parent, ref key } => {
+            format!("{}/{}", pointer(parent), escape(key))
+        }
+        serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => pointer(parent),
+    }
+}
+
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+// Values that disappear on re-serialization because they equal the
+// field's default.
+fn is_default_like(value: &Value) -> bool {
+    match *value {
+        Value::Null => true,
+        Value::Number(ref n) => n.as_f64() == Some(0.0),
+        Value::Array(ref a) => a.is_empty(),
+        _ => false,
+    }
+}
+
+fn same_number(a: &serde_json::Number, b: &serde_json::Number) -> bool {
+    if a == b {
+        return true;
+    }
+    match (a.as_f64(), b.as_f64()) {
+        // Whole prices come back as floats: 2 as 2.0.
+        (Some(x), Some(y)) => x == y,
+        _ => false,
+    }
+}
+
+fn report(out: &mut Vec<Deviation>, path: &str, kind: DeviationKind, value: &Value) {
+    out.push(Deviation {
+        path: path.to_string(),
+        kind,
+        value: value.clone(),
+    })
+}
+
+fn walk(
+    input: &Value,
+    full: Option<&Value>,
+    path: &mut String,
+    unknown: &[String],
+    outside: &HashSet<String>,
+    out: &mut Vec<Deviation>,
+) {
+    match *input {
+        Value::Object(ref map) => {
+            for (key, value) in map {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&escape(key));
+
+                let f = full.and_then(|f| f.get(key));
+                if unknown.iter().any(|u| u == path) || outside.contains(path.as_str()) {
+                    report(out, path, DeviationKind::UnknownField, value);
+                } else if f.is_none() {
+                    match *value {
+                        Value::Bool(false) => report(out, path, DeviationKind::BooleanLiteral, value),
+                        ref v if is_default_like(v) => (),
+                        _ => report(out, path, DeviationKind::Ignored, value),
+                    }
+                } else {
+                    walk(value, f, path, unknown, outside, out);
+                }
+
+                path.truncate(len);
+            }
+        }
+        Value::Array(ref items) => {
+            for (i, item) in items.iter().enumerate() {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&i.to_string());
+
+                let f = full.and_then(|f| f.get(i));
+                if f.is_none() {
+                    report(out, path, DeviationKind::Ignored, item);
+                } else {
+                    walk(item, f, path, unknown, outside, out);
+                }
+
+                path.truncate(len);
+            }
+        }
+        Value::Bool(_) => match full {
+            Some(Value::Bool(_)) => (),
+            Some(Value::Number(_)) => report(out, path, DeviationKind::BooleanLiteral, input),
+            _ => report(out, path, DeviationKind::Altered, input),
+        },
+        Value::Number(ref n) => match full {
+            Some(Value::Number(m)) if same_number(n, m) => (),
+            Some(Value::String(s)) if *s == n.to_string() => {
+                report(out, path, DeviationKind::NumberForString, input)
+            }
+            _ => report(out, path, DeviationKind::Altered, input),
+        },
+        Value::String(_) if outside.contains(path.as_str()) => {
+            report(out, path, DeviationKind::UnknownCategory, input)
+        }
+        Value::String(ref s) => match full {
+            Some(Value::String(t)) if s == t => (),
+            _ => report(out, path, DeviationKind::Altered, input),
+        },
+        Value::Null => (),
+    }
+}
+
+// A serializer that only records the JSON pointers of the values marked
+// with `serde_utils::OUTSIDE_SPEC`.
+struct Marks<'a> {
+    path: String,
+    out: &'a RefCell<HashSet<String>>,
+}
+
+struct MarksCompound<'a> {
+    path: String,
+    out: &'a RefCell<HashSet<String>>,
+    index: usize,
+    key: String,
+}
+
+impl<'a> Marks<'a> {
+    fn child(&self, key: &str) -> Marks<'a> {
+        Marks {
+            path: format!("{}/{}", self.path, escape(key)),
+            out: self.out,
+        }
+    }
+
+    fn compound(self) -> MarksCompound<'a> {
+        MarksCompound {
+            path: self.path,
+            out: self.out,
+            index: 0,
+            key: String::new(),
+        }
+    }
+}
+
+impl<'a> MarksCompound<'a> {
+    fn element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), serde_json::Error> {
+        let path = format!("{}/{}", self.path, self.index);
+        self.index += 1;
+        value.serialize(Marks { path, out: self.out })
+    }
+
+    fn field<T: ?Sized + Serialize>(&mut self, key: &str, value: &T) -> Result<(), serde_json::Error> {
+        let path = format!("{}/{}", self.path, escape(key));
+        value.serialize(Marks { path, out: self.out })
+    }
+}
+
+impl<'a> ser::Serializer for Marks<'a> {
+    type Ok = ();
+    type Error = serde_json::Error;
+    type SerializeSeq = MarksCompound<'a>;
+    type SerializeTuple = MarksCompound<'a>;
+    type SerializeTupleStruct = MarksCompound<'a>;
+    type SerializeTupleVariant = MarksCompound<'a>;
+    type SerializeMap = MarksCompound<'a>;
+    type SerializeStruct = MarksCompound<'a>;
+    type SerializeStructVariant = MarksCompound<'a>;
+
+    fn serialize_bool(self, _: bool) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_i64(self, _: i64) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_u64(self, _: u64) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_i32(self, _: i32) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_u8(self, _: u8) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_u16(self, _: u16) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_u32(self, _: u32) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_char(self, _: char) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_str(self, _: &str) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), serde_json::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), serde_json::Error> {
+        if name == serde_utils::OUTSIDE_SPEC {
+            self.out.borrow_mut().insert(self.path);
+            Ok(())
+        } else {
+            value.serialize(self)
+        }
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), serde_json::Error> {
+        value.serialize(self.child(variant))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<MarksCompound<'a>, serde_json::Error> {
+        Ok(self.compound())
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<MarksCompound<'a>, serde_json::Error> {
+        Ok(self.compound())
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<MarksCompound<'a>, serde_json::Error> {
+        Ok(self.compound())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<MarksCompound<'a>, serde_json::Error> {
+        Ok(self.child(variant).compound())
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<MarksCompound<'a>, serde_json::Error> {
+        Ok(self.compound())
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<MarksCompound<'a>, serde_json::Error> {
+        Ok(self.compound())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<MarksCompound<'a>, serde_json::Error> {
+        Ok(self.child(variant).compound())
+    }
+}
+
+impl<'a> ser::SerializeSeq for MarksCompound<'a> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), serde_json::Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTuple for MarksCompound<'a> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), serde_json::Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for MarksCompound<'a> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), serde_json::Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTupleVariant for MarksCompound<'a> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), serde_json::Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeMap for MarksCompound<'a> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    // Keys are written the way serde_json writes them.
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), serde_json::Error> {
+        self.key = match serde_json::to_value(key)? {
+            Value::String(s) => s,
+            other => other.to_string(),
+        };
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), serde_json::Error> {
+        let key = std::mem::take(&mut self.key);
+        self.field(&key, value)
+    }
+
+    fn end(self) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeStruct for MarksCompound<'a> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), serde_json::Error> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeStructVariant for MarksCompound<'a> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), serde_json::Error> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use current::{BidRequest, BidResponse};
+
+    #[test]
+    fn accepts_spec_input() {
+        let serialized = r#"{
+            "id": "1234",
+            "imp": [{"id": "1", "banner": {"w": 300, "h": 250}, "instl": 0, "bidfloor": 0.1}],
+            "test": 0,
+            "at": 1,
+            "bcat": ["IAB25", "IAB26-3"],
+            "ext": {"debug": true}
+        }"#;
+
+        let b: BidRequest = from_str(serialized).unwrap();
+
+        assert_eq!("1234", b.id)
+    }
+
+    #[test]
+    fn reports_every_deviation() {
+        let serialized = r#"{
+            "id": "1234",
+            "imp": [{"id": "1", "instl": true, "secure": "yes", "rwdd": 1}],
+            "test": false,
+            "at": 1,
+            "bcat": ["IAB25", "XYZ1"],
+            "acat": ["IAB1"]
+        }"#;
+
+        let mut found = match from_str::<BidRequest>(serialized) {
+            Err(Error::Deviations(ds)) => ds
+                .into_iter()
+                .map(|d| (d.path, d.kind))
+                .collect::<Vec<_>>(),
+            r => panic!("{:?}", r),
+        };
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            vec![
+                ("/acat".to_string(), DeviationKind::UnknownField),
+                ("/bcat/1".to_string(), DeviationKind::UnknownCategory),
+                ("/imp/0/instl".to_string(), DeviationKind::BooleanLiteral),
+                ("/imp/0/rwdd".to_string(), DeviationKind::UnknownField),
+                ("/imp/0/secure".to_string(), DeviationKind::Ignored),
+                ("/test".to_string(), DeviationKind::BooleanLiteral),
+            ],
+            found
+        )
+    }
+
+    #[test]
+    fn reports_numeric_ids() {
+        let serialized = r#"{
+            "id": "1234",
+            "seatbid": [{"bid": [{"id": 1, "impid": "1", "price": 1.5}]}]
+        }"#;
+
+        match from_str::<BidResponse>(serialized) {
+            Err(Error::Deviations(ds)) => assert_eq!(
+                vec![Deviation {
+                    path: "/seatbid/0/bid/0/id".to_string(),
+                    kind: DeviationKind::NumberForString,
+                    value: Value::from(1),
+                }],
+                ds
+            ),
+            r => panic!("{:?}", r),
+        }
+    }
+}
@@ -55,6 +55,10 @@ pub struct App {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
 
 #[cfg(test)]
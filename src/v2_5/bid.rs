@@ -1,4 +1,9 @@
+use serde_json;
+
 use super::category::Category;
+use super::native::Native;
+use super::price::Price;
+use native::{self, v1_2};
 use serde_utils;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -7,7 +12,7 @@ pub struct Bid {
     pub id: String,
     #[serde(rename = "impid", deserialize_with = "serde_utils::anything_to_string")]
     pub imp_id: String,
-    pub price: f64,
+    pub price: Price,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub nurl: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -56,14 +61,17 @@ pub struct Bid {
     pub exp: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
 
 impl Bid {
-    pub fn new(id: String, imp_id: String, price: f64) -> Bid {
+    pub fn new(id: String, imp_id: String, price: Price) -> Bid {
         Bid {
-            id: id,
-            imp_id: imp_id,
-            price: price,
+            id,
+            imp_id,
+            price,
             nurl: None,
             burl: None,
             lurl: None,
@@ -88,6 +96,96 @@ impl Bid {
             hratio: None,
             exp: None,
             ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
+        }
+    }
+
+    // Sets adm to the JSON-encoded native response. It is wrapped in
+    // `{"native": ...}` if the native request this bid answers is version
+    // 1.0 or 1.1, going by `Native.ver`, then the `ver` inside the request
+    // payload. A request without either is taken to be 1.0 or 1.1 if its
+    // payload is wrapped, else the response's own `ver` decides.
+    pub fn with_native_response(mut self, response: &v1_2::Response, request: &Native) -> Result<Bid, serde_json::Error> {
+        let ver = request
+            .ver
+            .clone()
+            .or_else(|| request.native_request().ok().and_then(|r| r.ver));
+        let wrapped = match ver {
+            Some(ref v) => native::is_wrapped_version(Some(v)),
+            None => native::is_wrapped(&request.request) || native::is_wrapped_version(response.ver.as_deref()),
+        };
+        self.adm = Some(native::encode_as(response, wrapped)?);
+        Ok(self)
+    }
+
+    // The native response in adm, with or without the wrapper; `None` if
+    // there is no adm.
+    pub fn native_response(&self) -> Option<Result<v1_2::Response, serde_json::Error>> {
+        self.adm.as_ref().map(|adm| native::decode(adm))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESPONSE: &str = r#"{
+        "ver": "1.2",
+        "assets": [
+            {"id": 1, "title": {"text": "Learn about this awesome thing"}},
+            {"id": 2, "img": {"url": "https://example.com/a.jpg", "w": 1200, "h": 627}},
+            {"id": 3, "data": {"value": "5 stars"}, "link": {"url": "https://example.com/reviews"}}
+        ],
+        "link": {"url": "https://example.com/landing", "clicktrackers": ["https://example.com/click"]},
+        "eventtrackers": [{"event": 1, "method": 1, "url": "https://example.com/imp"}]
+    }"#;
+
+    fn request(payload: &str, ver: Option<&str>) -> Native {
+        let mut native = Native::new(payload.to_string());
+        native.ver = ver.map(str::to_string);
+        native
+    }
+
+    #[test]
+    fn native_response_round_trip() {
+        let response: v1_2::Response = serde_json::from_str(RESPONSE).unwrap();
+        let bid = Bid::new("1".to_string(), "1".to_string(), Price::from(1.5))
+            .with_native_response(&response, &request(r#"{"ver": "1.2", "assets": []}"#, None))
+            .unwrap();
+
+        assert!(!bid.adm.as_ref().unwrap().contains("null"));
+        assert!(bid.adm.as_ref().unwrap().starts_with(r#"{"ver":"1.2""#));
+        assert_eq!(response, bid.native_response().unwrap().unwrap());
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(RESPONSE).unwrap(),
+            serde_json::from_str::<serde_json::Value>(bid.adm.as_ref().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn wrapped_for_old_requests() {
+        let response: v1_2::Response = serde_json::from_str(RESPONSE).unwrap();
+
+        for native in &[
+            request(r#"{"native": {"ver": "1.1", "assets": []}}"#, None),
+            request(r#"{"assets": []}"#, Some("1.0")),
+            // No version anywhere, but the request itself is wrapped.
+            request(r#"{"native": {"assets": []}}"#, None),
+        ] {
+            let bid = Bid::new("1".to_string(), "1".to_string(), Price::from(1.5))
+                .with_native_response(&response, native)
+                .unwrap();
+
+            assert!(bid.adm.as_ref().unwrap().starts_with(r#"{"native":"#));
+            assert_eq!(response, bid.native_response().unwrap().unwrap());
         }
     }
+
+    #[test]
+    fn no_adm() {
+        assert!(Bid::new("1".to_string(), "1".to_string(), Price::from(1.5))
+            .native_response()
+            .is_none());
+    }
 }
@@ -8,6 +8,9 @@
 
 use serde;
 
+use currency;
+use ext::{self, Extension, Typed};
+use parse;
 use serde_utils;
 
 use super::app::App;
@@ -38,8 +41,12 @@ pub enum AuctionType {
 // recommended, but only one applies to a given bid request depending on whether
 // the media is browser-based web content or a non-browser application,
 // respectively.
+//
+// The ext type defaults to the untyped map; see the `ext` module for
+// decoding it into a known schema.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct BidRequest {
+#[serde(bound(deserialize = "E: Extension"))]
+pub struct BidRequest<E = serde_utils::Ext> {
     // Required. Unique ID of the bid request, provided by the exchange.
     pub id: String,
 
@@ -162,14 +169,22 @@ pub struct BidRequest {
     pub regulations: Option<Regulations>,
 
     // Placeholder for exchange-specific extensions to OpenRTB.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub ext: Option<serde_utils::Ext>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "ext::deserialize_validated"
+    )]
+    pub ext: Option<E>,
+
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
 
 impl BidRequest {
     pub fn new(id: String) -> BidRequest {
         BidRequest {
-            id: id,
+            id,
             imp: vec![],
             site: None,
             app: None,
@@ -189,11 +204,80 @@ impl BidRequest {
             source: None,
             regulations: None,
             ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
         }
     }
 
+    // Parses a JSON bid request; errors point at the offending value.
+    pub fn parse(bytes: &[u8]) -> Result<BidRequest, parse::Error> {
+        parse::from_slice(bytes)
+    }
+
+    // Decodes and validates the ext map once, as `E`, keeping the keys
+    // `E` doesn't model.
+    pub fn with_typed_ext<E>(self) -> Result<BidRequest<Typed<E>>, ext::Error>
+    where
+        E: Extension,
+    {
+        self.try_map_ext(Typed::from_map)
+    }
+}
+
+impl<E> BidRequest<E> {
     pub fn validate(&self) -> bool {
-        self.imp.len() > 0
+        !self.imp.is_empty()
+    }
+
+    // Whether bids may be priced in `currency`. Without a cur list the
+    // exchange takes a single currency, USD.
+    pub fn allows_currency(&self, currency: &str) -> bool {
+        if self.currency.is_empty() {
+            currency == currency::DEFAULT
+        } else {
+            self.currency.iter().any(|c| c == currency)
+        }
+    }
+
+    // Converts back to a request with an untyped ext map.
+    pub fn into_untyped_ext(self) -> Result<BidRequest, ext::Error>
+    where
+        E: Extension,
+    {
+        self.try_map_ext(|x| ext::encode(&x))
+    }
+
+    fn try_map_ext<T, F>(self, f: F) -> Result<BidRequest<T>, ext::Error>
+    where
+        F: FnOnce(E) -> Result<T, ext::Error>,
+    {
+        Ok(BidRequest {
+            id: self.id,
+            imp: self.imp,
+            site: self.site,
+            app: self.app,
+            device: self.device,
+            user: self.user,
+            test: self.test,
+            auction_type: self.auction_type,
+            tmax: self.tmax,
+            seat_whitelist: self.seat_whitelist,
+            seat_blocklist: self.seat_blocklist,
+            all_imps: self.all_imps,
+            currency: self.currency,
+            language_whitelist: self.language_whitelist,
+            category_blocklist: self.category_blocklist,
+            advertiser_blocklist: self.advertiser_blocklist,
+            app_blocklist: self.app_blocklist,
+            source: self.source,
+            regulations: self.regulations,
+            ext: match self.ext {
+                Some(x) => Some(f(x)?),
+                None => None,
+            },
+            #[cfg(feature = "unknown-fields")]
+            other: self.other,
+        })
     }
 }
 
@@ -231,7 +315,7 @@ mod tests {
 
     #[test]
     fn serialization_skip_fields() {
-        let b = BidRequest {
+        let b: BidRequest = BidRequest {
             id: "1234".to_string(),
             imp: vec![],
             site: None,
@@ -252,6 +336,8 @@ mod tests {
             source: None,
             regulations: None,
             ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
         };
 
         let expected = r#"{"id":"1234","imp":[],"at":1}"#;
@@ -275,7 +361,7 @@ mod tests {
             Err(e) => panic!("{:?}", e),
         };
 
-        let expected = BidRequest {
+        let expected: BidRequest = BidRequest {
             id: "1234".to_string(),
             imp: vec![],
             site: None,
@@ -296,9 +382,36 @@ mod tests {
             source: None,
             regulations: None,
             ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
         };
 
         assert_eq!(expected.id, b.id);
         assert_eq!(expected.auction_type, b.auction_type);
     }
+
+    #[test]
+    #[cfg(feature = "unknown-fields")]
+    fn round_trip_unknown_fields() {
+        let serialized = r#"{
+            "id": "1234",
+            "imp": [{"id": "1", "banner": {"w": 300, "h": 250, "vcm": 1}, "rwdd": 1}],
+            "site": {"id": "102855", "domain": "www.foobar.com"},
+            "device": {"ua": "Mozilla/5.0", "geo": {"country": "USA", "ipservice": 3, "zip": "10001"}, "sua": {"mobile": 0}},
+            "at": 1,
+            "acat": ["IAB1"],
+            "dooh": {"id": "screen-1"}
+        }"#;
+
+        let b: BidRequest = serde_json::from_str(serialized).unwrap();
+        assert_eq!(
+            Some(&serde_json::Value::from(vec!["IAB1"])),
+            b.other.get("acat")
+        );
+
+        let expected: serde_json::Value = serde_json::from_str(serialized).unwrap();
+        let reserialized = serde_json::to_value(&b).unwrap();
+
+        assert_eq!(expected, reserialized)
+    }
 }
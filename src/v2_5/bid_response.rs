@@ -6,7 +6,13 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use super::bid::Bid;
+use super::bid_request::BidRequest;
+use super::no_bid_reason::NoBidReason;
+use super::price::Price;
 use super::seat_bid::SeatBid;
+use currency::{self, CurrencyConverter};
+use parse;
 use serde_utils;
 
 // 4.2.1 Object: BidResponse
@@ -39,25 +45,76 @@ pub struct BidResponse {
     pub custom_data: Option<String>,
 
     #[serde(rename = "nbr", skip_serializing_if = "Option::is_none")]
-    pub no_bidding_reason: Option<u32>,
+    pub no_bidding_reason: Option<NoBidReason>,
 
     // Placeholder for exchange-specific extensions to OpenRTB.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
 
 impl BidResponse {
     pub fn new(id: String) -> BidResponse {
         BidResponse {
-            id: id,
+            id,
             seat_bid: vec![],
             bid_id: None,
             currency: None,
             custom_data: None,
             no_bidding_reason: None,
             ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
+        }
+    }
+
+    // Parses a JSON bid response; errors point at the offending value.
+    pub fn parse(bytes: &[u8]) -> Result<BidResponse, parse::Error> {
+        parse::from_slice(bytes)
+    }
+
+    // A response declining to bid on `request`, giving the reason.
+    pub fn no_bid<E>(request: &BidRequest<E>, reason: NoBidReason) -> BidResponse {
+        BidResponse {
+            no_bidding_reason: Some(reason),
+            ..BidResponse::new(request.id.clone())
         }
     }
+
+    // A response without bids is a no-bid, whether or not it gives a
+    // reason.
+    pub fn is_no_bid(&self) -> bool {
+        self.seat_bid.iter().all(|s| s.bid.is_empty())
+    }
+
+    // Whether an HTTP reply to a bid request is a no-bid before it is
+    // parsed: a 204 status or an empty body.
+    pub fn is_no_bid_reply(status: u16, body: &[u8]) -> bool {
+        status == 204 || body.iter().all(u8::is_ascii_whitespace)
+    }
+
+    // The currency of every bid price: cur, or USD when left out.
+    pub fn effective_currency(&self) -> &str {
+        self.currency.as_deref().unwrap_or(currency::DEFAULT)
+    }
+
+    // Whether the response is priced in a currency `request` allowed.
+    pub fn currency_allowed_by<E>(&self, request: &BidRequest<E>) -> bool {
+        request.allows_currency(self.effective_currency())
+    }
+
+    // Every bid with its price converted to `currency`.
+    pub fn bids_in(&self, currency: &str, converter: &dyn CurrencyConverter) -> Result<Vec<(&Bid, Price)>, currency::Error> {
+        let from = self.effective_currency();
+        self.seat_bid
+            .iter()
+            .flat_map(|s| &s.bid)
+            .map(|b| Ok((b, converter.convert(b.price, from, currency)?)))
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -75,6 +132,8 @@ mod tests {
             custom_data: None,
             no_bidding_reason: None,
             ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
         };
 
         let expected = r#"{"id":"1234"}"#;
@@ -104,8 +163,67 @@ mod tests {
             custom_data: None,
             no_bidding_reason: None,
             ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
         };
 
         assert_eq!(expected.id, b.id);
     }
+
+    #[test]
+    fn no_bid() {
+        let request = BidRequest::new("req-1".to_string());
+        let response = BidResponse::no_bid(&request, NoBidReason::UnmatchedUser);
+
+        assert!(response.is_no_bid());
+        assert_eq!(r#"{"id":"req-1","nbr":8}"#, serde_json::to_string(&response).unwrap());
+
+        let with_empty_seat: BidResponse = serde_json::from_str(r#"{"id":"1","seatbid":[{"bid":[]}]}"#).unwrap();
+        assert!(with_empty_seat.is_no_bid());
+        let with_bid: BidResponse =
+            serde_json::from_str(r#"{"id":"1","seatbid":[{"bid":[{"id":"b","impid":"1","price":1}]}]}"#).unwrap();
+        assert!(!with_bid.is_no_bid());
+
+        assert!(BidResponse::is_no_bid_reply(204, b""));
+        assert!(BidResponse::is_no_bid_reply(200, b" \n"));
+        assert!(!BidResponse::is_no_bid_reply(200, br#"{"id":"1"}"#));
+    }
+
+    #[test]
+    fn currency() {
+        let mut rates = currency::RateTable::default();
+        rates.insert("USD", "EUR", 0.5);
+        let mut response: BidResponse = serde_json::from_str(
+            r#"{"id":"1","seatbid":[{"bid":[{"id":"a","impid":"1","price":1}]},{"bid":[{"id":"b","impid":"2","price":3}]}]}"#,
+        )
+        .unwrap();
+        let mut request = BidRequest::new("1".to_string());
+
+        let prices = |r: &BidResponse, cur| -> Vec<f64> { r.bids_in(cur, &rates).unwrap().iter().map(|b| b.1.to_f64()).collect() };
+        assert_eq!(vec![0.5, 1.5], prices(&response, "EUR"));
+        assert!(response.currency_allowed_by(&request));
+
+        response.currency = Some("EUR".to_string());
+        assert_eq!(vec![2.0, 6.0], prices(&response, "USD"));
+        assert!(response.bids_in("GBP", &rates).is_err());
+        assert!(!response.currency_allowed_by(&request));
+        request.currency = vec!["USD".to_string(), "EUR".to_string()];
+        assert!(response.currency_allowed_by(&request));
+    }
+
+    #[test]
+    fn bids_in_own_currency_needs_no_rate() {
+        struct NoRates;
+        impl CurrencyConverter for NoRates {
+            fn rate(&self, _: &str, _: &str) -> Option<f64> {
+                None
+            }
+        }
+        let response: BidResponse =
+            serde_json::from_str(r#"{"id":"1","cur":"EUR","seatbid":[{"bid":[{"id":"a","impid":"1","price":1.5}]}]}"#)
+                .unwrap();
+
+        assert_eq!(Price::from_micros(1_500_000), response.bids_in("EUR", &NoRates).unwrap()[0].1);
+        assert!(response.bids_in("USD", &NoRates).is_err());
+    }
 }
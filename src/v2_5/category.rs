@@ -11,6 +11,8 @@ use std::str::FromStr;
 use phf::phf_map;
 use serde;
 
+use serde_utils;
+
 macro_rules! categories {
     ( $( $parent:ident $pval:expr     // parent category
          $(, $sub:ident $sval:expr )* // optional subcategories separated by comma
@@ -485,7 +487,53 @@ impl serde::Serialize for Category {
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.value())
+        match *self {
+            Category::Unknown(ref s) => serde::Serialize::serialize(&serde_utils::OutsideSpec(s), serializer),
+            _ => serializer.serialize_str(&self.value()),
+        }
+    }
+}
+
+struct CategoryVisitor;
+
+impl<'de> serde::de::Visitor<'de> for CategoryVisitor {
+    type Value = Category;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an IAB content category string")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Category::from_value(value))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        match TO_CATEGORY.get(value.as_str()).cloned() {
+            Some(c) => Ok(c),
+            None => Ok(Category::Unknown(value)),
+        }
+    }
+}
+
+impl Category {
+    // Whether this is `other` or one of its subcategories, e.g. IAB1-6
+    // is within IAB1.
+    pub fn is_within(&self, other: &Category) -> bool {
+        let (this, other) = (self.value(), other.value());
+        this == other || (this.starts_with(&other) && this[other.len()..].starts_with('-'))
+    }
+
+    fn from_value(s: &str) -> Category {
+        match TO_CATEGORY.get(s).cloned() {
+            Some(c) => c,
+            None => Category::Unknown(FromStr::from_str(s).unwrap()),
+        }
     }
 }
 
@@ -494,13 +542,7 @@ impl<'de> serde::Deserialize<'de> for Category {
     where
         D: serde::Deserializer<'de>,
     {
-        match serde::Deserialize::deserialize(deserializer) {
-            Ok(s) => match TO_CATEGORY.get(s).cloned() {
-                Some(c) => Ok(c),
-                None => Ok(Category::Unknown(FromStr::from_str(s).unwrap())),
-            },
-            Err(e) => Err(e),
-        }
+        deserializer.deserialize_str(CategoryVisitor)
     }
 }
 
@@ -509,6 +551,17 @@ mod tests {
     use super::*;
     use serde_json;
 
+    #[test]
+    fn within_parent() {
+        let music = Category::ArtsAndEntertainment(ArtsAndEntertainment::Music);
+        let parent = Category::ArtsAndEntertainment(ArtsAndEntertainment::ArtsAndEntertainment);
+
+        assert!(music.is_within(&parent));
+        assert!(music.is_within(&music));
+        assert!(!parent.is_within(&music));
+        assert!(!Category::Unknown("IAB10".to_string()).is_within(&parent));
+    }
+
     #[test]
     fn serialize_parent() {
         let c = Category::ArtsAndEntertainment(ArtsAndEntertainment::ArtsAndEntertainment);
@@ -554,6 +607,15 @@ mod tests {
         assert_eq!(expected, serialized)
     }
 
+    #[test]
+    fn deserialize_owned() {
+        let value = serde_json::Value::String("IAB1-1".to_string());
+        let expected = Category::ArtsAndEntertainment(ArtsAndEntertainment::BooksAndLiterature);
+        let c: Category = serde_json::from_value(value).unwrap();
+
+        assert_eq!(expected, c)
+    }
+
     #[test]
     fn deserialize_unknown() {
         let serialized = r#""1234""#;
@@ -21,4 +21,7 @@ pub struct Data {
     // Array of Segment (Section 3.2.22) objects that contain the actual data values
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub segment: Vec<Segment>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
@@ -22,7 +22,12 @@ pub struct Device {
         deserialize_with = "serde_utils::u8_to_mbool"
     )]
     pub dnt: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serde_utils::mbool_to_u8",
+        deserialize_with = "serde_utils::u8_to_mbool"
+    )]
     pub lmt: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ip: Option<String>,
@@ -88,4 +93,7 @@ pub struct Device {
     pub macmd5: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
@@ -0,0 +1,144 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use serde;
+
+use serde_utils;
+
+// Sources of some widely used identity providers.
+pub const UID2_SOURCE: &str = "uidapi.com";
+pub const ID5_SOURCE: &str = "id5-sync.com";
+pub const SHARED_ID_SOURCE: &str = "pubcid.org";
+
+// Extended identifiers support in the OpenRTB specification allows buyers
+// to use audience data in real-time bidding. This object can contain one
+// or more UIDs from a single source or a technology provider. The
+// exchange should ensure that business agreements allow for the sending
+// of this data. Sent in user.ext.eids in 2.5 and user.eids in 2.6.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Eid {
+    // Source or technology provider responsible for the set of included
+    // IDs. Expressed as a top-level domain.
+    pub source: String,
+
+    // Array of extended ID UID objects from the given source.
+    pub uids: Vec<Uid>,
+
+    // Placeholder for vendor specific extensions to this object.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<serde_utils::Ext>,
+
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
+
+// This object contains a single user identifier provided as part of
+// extended identifiers.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Uid {
+    // The identifier for the user.
+    pub id: String,
+
+    // Type of user agent the ID is from. It is highly recommended to set
+    // this, as many DSPs separate app-native IDs from browser-based IDs
+    // and require a type value for ID resolution.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub atype: Option<AgentType>,
+
+    // Placeholder for vendor specific extensions to this object.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<serde_utils::Ext>,
+
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
+
+// Agent Types: the user agent types a user identifier is from.
+#[derive(Debug, PartialEq, Clone)]
+pub enum AgentType {
+    // An ID which is tied to a specific web browser or device
+    // (cookie-based, probabilistic, or other).
+    Device,
+    // In-app impressions, which will typically contain a type of device
+    // ID (or rather, the privacy-compliant version of device ID).
+    InApp,
+    // A person-based ID, i.e., that is the same across devices.
+    Person,
+    // Values below 500 the spec doesn't define, which it reserves for
+    // future use.
+    Reserved(u32),
+    // Vendor-specific values, 500 and up.
+    VendorSpecific(u32),
+}
+
+impl Eid {
+    // The id of the first uid, which is the one most consumers use.
+    pub fn first_id(&self) -> Option<&str> {
+        self.uids.first().map(|u| u.id.as_str())
+    }
+}
+
+impl serde::Serialize for AgentType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match *self {
+            AgentType::Device => serializer.serialize_u32(1),
+            AgentType::InApp => serializer.serialize_u32(2),
+            AgentType::Person => serializer.serialize_u32(3),
+            AgentType::Reserved(t) | AgentType::VendorSpecific(t) => serializer.serialize_u32(t),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for AgentType {
+    fn deserialize<D>(deserializer: D) -> Result<AgentType, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match serde::Deserialize::deserialize(deserializer) {
+            Ok(1) => Ok(AgentType::Device),
+            Ok(2) => Ok(AgentType::InApp),
+            Ok(3) => Ok(AgentType::Person),
+            Ok(t) if t >= 500 => Ok(AgentType::VendorSpecific(t)),
+            Ok(t) => Ok(AgentType::Reserved(t)),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn deserialize_eid() {
+        let serialized = r#"{"source":"uidapi.com","uids":[{"id":"A4AAAABlh","atype":3,"ext":{"rtiPartner":"UID2"}}]}"#;
+        let e: Eid = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(Some(AgentType::Person), e.uids[0].atype);
+        assert_eq!(Some("A4AAAABlh"), e.first_id());
+        assert_eq!(serialized, serde_json::to_string(&e).unwrap());
+    }
+
+    #[test]
+    fn agent_type_ranges() {
+        assert_eq!(
+            AgentType::VendorSpecific(501),
+            serde_json::from_str("501").unwrap()
+        );
+        assert_eq!(AgentType::Reserved(4), serde_json::from_str("4").unwrap());
+        assert_eq!("499", serde_json::to_string(&AgentType::Reserved(499)).unwrap());
+        assert_eq!(AgentType::Reserved(0), serde_json::from_str("0").unwrap());
+        assert!(serde_json::from_str::<AgentType>("-1").is_err());
+    }
+}
@@ -40,6 +40,10 @@ pub struct Format {
     // Placeholder for exchange-specific extensions to OpenRTB.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
 
 #[cfg(test)]
@@ -56,6 +60,8 @@ mod tests {
             h_ratio: None,
             w_min: None,
             ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
         };
 
         let expected = r#"{}"#;
@@ -82,6 +88,8 @@ mod tests {
             h_ratio: None,
             w_min: None,
             ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
         };
 
         assert_eq!(expected, f);
@@ -6,6 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use currency::{self, CurrencyConverter};
 use serde_utils;
 
 use super::audio::Audio;
@@ -13,6 +14,7 @@ use super::banner::Banner;
 use super::metric::Metric;
 use super::native::Native;
 use super::pmp::PMP;
+use super::price::Price;
 use super::video::Video;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -56,7 +58,7 @@ pub struct Imp {
     pub tag_id: Option<String>,
 
     #[serde(rename = "bidfloor", skip_serializing_if = "Option::is_none")]
-    pub bid_floor: Option<f32>,
+    pub bid_floor: Option<Price>,
 
     #[serde(rename = "bidfloorcur", skip_serializing_if = "Option::is_none")]
     pub bid_floor_cur: Option<String>,
@@ -86,6 +88,17 @@ pub struct Imp {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
+
+impl Imp {
+    // The floor converted to `currency`; bidfloorcur defaults to USD.
+    pub fn floor_in(&self, currency: &str, converter: &dyn CurrencyConverter) -> Result<Option<Price>, currency::Error> {
+        currency::floor_in(self.bid_floor, self.bid_floor_cur.as_deref(), currency, converter)
+    }
 }
 
 #[cfg(test)]
@@ -111,6 +124,8 @@ mod tests {
             bid_floor_cur: None,
             secure: None,
             ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
             exp: None,
         };
 
@@ -127,4 +142,19 @@ mod tests {
             "7a5156a2-50f5-4dea-9eeb-a767f975d500",
         )
     }
+
+    #[test]
+    fn floor_in() {
+        let mut rates = currency::RateTable::default();
+        rates.insert("USD", "EUR", 0.5);
+        let mut imp: Imp = serde_json::from_str(r#"{"id":"1","bidfloor":2}"#).unwrap();
+
+        assert_eq!(Some(Price::from_micros(1_000_000)), imp.floor_in("EUR", &rates).unwrap());
+        assert_eq!(Some(Price::from_micros(2_000_000)), imp.floor_in("USD", &rates).unwrap());
+        imp.bid_floor_cur = Some("EUR".to_string());
+        assert_eq!(Some(Price::from_micros(4_000_000)), imp.floor_in("USD", &rates).unwrap());
+        assert!(imp.floor_in("GBP", &rates).is_err());
+        imp.bid_floor = None;
+        assert_eq!(None, imp.floor_in("GBP", &rates).unwrap());
+    }
 }
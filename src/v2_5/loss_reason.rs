@@ -0,0 +1,83 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// 5.25 Loss Reason Codes
+enum_list_module! {
+    LossReason u32:
+        BidWon                                      0,
+        InternalError                               1,
+        ImpressionOpportunityExpired                2,
+        InvalidBidResponse                          3,
+        InvalidDealId                               4,
+        InvalidAuctionId                            5,
+        InvalidAdvertiserDomain                     6,
+        MissingMarkup                               7,
+        MissingCreativeId                           8,
+        MissingBidPrice                             9,
+        MissingMinimumCreativeApprovalData          10,
+        BelowAuctionFloor                           100,
+        BelowDealFloor                              101,
+        LostToHigherBid                             102,
+        LostToPmpDeal                               103,
+        BuyerSeatBlocked                            104,
+        CreativeFilteredGeneral                     200,
+        CreativeFilteredPendingProcessing           201,
+        CreativeFilteredDisapproved                 202,
+        CreativeFilteredSizeNotAllowed              203,
+        CreativeFilteredIncorrectFormat             204,
+        CreativeFilteredAdvertiserExclusions        205,
+        CreativeFilteredAppBundleExclusions         206,
+        CreativeFilteredNotSecure                   207,
+        CreativeFilteredLanguageExclusions          208,
+        CreativeFilteredCategoryExclusions          209,
+        CreativeFilteredCreativeAttributeExclusions 210,
+        CreativeFilteredAdTypeExclusions            211,
+        CreativeFilteredAnimationTooLong            212,
+        CreativeFilteredNotAllowedInPmpDeal         213;
+        ExchangeSpecific                            1000..
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn serialize_good() {
+        let x = LossReason::LostToHigherBid;
+        let expected = "102";
+        let serialized = serde_json::to_string(&x).unwrap();
+
+        assert_eq!(expected, serialized)
+    }
+
+    #[test]
+    fn deserialize_good() {
+        let serialized = "213";
+        let expected = LossReason::CreativeFilteredNotAllowedInPmpDeal;
+        let x = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(expected, x)
+    }
+
+    #[test]
+    fn deserialize_exchange_specific() {
+        let x: LossReason = serde_json::from_str("1001").unwrap();
+
+        assert_eq!(LossReason::ExchangeSpecific(1001), x);
+        assert_eq!("1001", serde_json::to_string(&x).unwrap())
+    }
+
+    #[test]
+    fn deserialize_bad() {
+        let serialized = "500";
+        let res: Result<LossReason, serde_json::Error> = serde_json::from_str(serialized);
+
+        assert!(res.is_err())
+    }
+}
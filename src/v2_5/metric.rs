@@ -6,5 +6,12 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#[cfg(feature = "unknown-fields")]
+use serde_utils;
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct Metric {}
+pub struct Metric {
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
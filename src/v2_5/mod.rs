@@ -17,12 +17,16 @@ mod bid_response;
 mod category;
 mod data;
 mod device;
+mod eid;
 mod format;
 mod geo;
 mod imp;
+mod loss_reason;
 mod metric;
 mod native;
+mod no_bid_reason;
 mod pmp;
+mod price;
 mod publisher;
 mod regulations;
 mod seat_bid;
@@ -33,10 +37,14 @@ mod supply_chain;
 mod user;
 mod video;
 
+// Every enum module also defines an IntoEnumError, which the globs make
+// ambiguous.
+#[allow(ambiguous_glob_reexports)]
 pub use self::ad_position::*;
 pub use self::app::*;
 pub use self::audio::*;
 pub use self::banner::*;
+#[allow(ambiguous_glob_reexports)]
 pub use self::banner_ad_type::*;
 pub use self::bid::*;
 pub use self::bid_request::*;
@@ -44,12 +52,16 @@ pub use self::bid_response::*;
 pub use self::category::*;
 pub use self::data::*;
 pub use self::device::*;
+pub use self::eid::*;
 pub use self::format::*;
 pub use self::geo::*;
 pub use self::imp::*;
+pub use self::loss_reason::LossReason;
 pub use self::metric::*;
 pub use self::native::*;
+pub use self::no_bid_reason::NoBidReason;
 pub use self::pmp::*;
+pub use self::price::{Price, PriceError};
 pub use self::publisher::*;
 pub use self::regulations::*;
 pub use self::seat_bid::*;
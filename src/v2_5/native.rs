@@ -6,10 +6,17 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::ops::Deref;
+
+use serde_json;
+
+use native::{self as native_markup, v1_2};
 use serde_utils;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Native {
+    // Request payload complying with the Native Ad Specification,
+    // JSON-encoded. See `native_request` for the typed form.
     pub request: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ver: Option<String>,
@@ -19,4 +26,144 @@ pub struct Native {
     pub battr: Vec<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
+
+// A Native object with its request parsed once, for code that reads the
+// request more than once. It borrows the object, so `request` can't be
+// reassigned while the parsed form is in use.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedNative<'a> {
+    pub native: &'a Native,
+    pub request: v1_2::Request,
+}
+
+impl<'a> Deref for ParsedNative<'a> {
+    type Target = v1_2::Request;
+
+    fn deref(&self) -> &v1_2::Request {
+        &self.request
+    }
+}
+
+impl Native {
+    pub fn new(request: String) -> Native {
+        Native {
+            request,
+            ver: None,
+            api: vec![],
+            battr: vec![],
+            ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
+        }
+    }
+
+    // A Native object carrying `request`, with `ver` taken from it.
+    pub fn from_request(request: &v1_2::Request) -> Result<Native, serde_json::Error> {
+        let mut native = Native::new(String::new());
+        native.ver = request.ver.clone();
+        native.set_native_request(request)?;
+        Ok(native)
+    }
+
+    // The embedded native request, with or without the 1.0/1.1
+    // `{"native": ...}` wrapper. It is parsed on every call; see `parsed`.
+    pub fn native_request(&self) -> Result<v1_2::Request, serde_json::Error> {
+        native_markup::decode(&self.request)
+    }
+
+    // This object with its request parsed once.
+    pub fn parsed(&self) -> Result<ParsedNative<'_>, serde_json::Error> {
+        Ok(ParsedNative {
+            native: self,
+            request: self.native_request()?,
+        })
+    }
+
+    // Replaces the embedded request. It is wrapped if `ver` (or, if unset,
+    // the request's own `ver`) is 1.0 or 1.1.
+    pub fn set_native_request(&mut self, request: &v1_2::Request) -> Result<(), serde_json::Error> {
+        let ver = self.ver.as_ref().or(request.ver.as_ref()).map(String::as_str);
+        self.request = native_markup::encode(request, ver)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use native::v1_2::PlacementType;
+
+    const REQUEST: &str = r#"{"ver": "1.2", "plcmttype": 1, "assets": [{"id": 1, "required": 1, "title": {"len": 90}}]}"#;
+
+    #[test]
+    fn unwrapped_request() {
+        let native = Native::new(REQUEST.to_string());
+
+        let request = native.native_request().unwrap();
+        assert_eq!(Some(PlacementType::InFeed), request.placement_type);
+        assert_eq!(90, request.assets[0].title.as_ref().unwrap().len);
+    }
+
+    #[test]
+    fn privacy_flag_wire_format() {
+        let native = Native::new(REQUEST.replace("\"plcmttype\": 1", "\"privacy\": 1"));
+
+        let request = native.native_request().unwrap();
+        assert_eq!(Some(true), request.privacy);
+        assert!(serde_json::to_string(&request).unwrap().contains(r#""privacy":1"#));
+    }
+
+    #[test]
+    fn wrapped_request() {
+        let native = Native::new(format!(r#"{{"native": {}}}"#, REQUEST));
+
+        assert_eq!(1, native.native_request().unwrap().assets.len());
+    }
+
+    #[test]
+    fn parsed_and_reassigned() {
+        let mut native = Native {
+            request: REQUEST.to_string(),
+            ver: Some("1.2".to_string()),
+            api: vec![],
+            battr: vec![],
+            ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
+        };
+        {
+            let parsed = native.parsed().unwrap();
+            assert_eq!(Some(PlacementType::InFeed), parsed.placement_type);
+            assert_eq!(Some("1.2".to_string()), parsed.native.ver);
+        }
+
+        native.request = REQUEST.replace("\"plcmttype\": 1", "\"plcmttype\": 4");
+        assert_eq!(
+            Some(PlacementType::Recommendation),
+            native.native_request().unwrap().placement_type
+        );
+    }
+
+    #[test]
+    fn set_request_follows_version() {
+        let mut request = Native::new(REQUEST.to_string()).native_request().unwrap();
+        request.ver = Some("1.1".to_string());
+
+        let mut native = Native::from_request(&request).unwrap();
+        assert!(native.request.starts_with(r#"{"native":"#));
+        assert_eq!(request, native.native_request().unwrap());
+
+        native.ver = Some("1.2".to_string());
+        native.set_native_request(&request).unwrap();
+        assert!(native.request.starts_with(r#"{"ver":"#));
+
+        let serialized = serde_json::to_string(&native).unwrap();
+        let decoded: Native = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(native, decoded);
+        assert_eq!(request, decoded.native_request().unwrap());
+    }
 }
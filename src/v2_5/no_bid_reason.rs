@@ -0,0 +1,66 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// 5.24 No-Bid Reason Codes
+enum_list_module! {
+    NoBidReason u32:
+        UnknownError              0,
+        TechnicalError            1,
+        InvalidRequest            2,
+        KnownWebSpider            3,
+        SuspectedNonHumanTraffic  4,
+        CloudDataCenterOrProxyIp  5,
+        UnsupportedDevice         6,
+        BlockedPublisherOrSite    7,
+        UnmatchedUser             8,
+        DailyReaderCapMet         9,
+        DailyDomainCapMet         10;
+        // The spec reserves no range, so any other value is taken as
+        // exchange-specific.
+        ExchangeSpecific          11..
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn serialize_good() {
+        let x = NoBidReason::SuspectedNonHumanTraffic;
+        let expected = "4";
+        let serialized = serde_json::to_string(&x).unwrap();
+
+        assert_eq!(expected, serialized)
+    }
+
+    #[test]
+    fn deserialize_good() {
+        let serialized = "8";
+        let expected = NoBidReason::UnmatchedUser;
+        let x = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(expected, x)
+    }
+
+    #[test]
+    fn deserialize_exchange_specific() {
+        let x: NoBidReason = serde_json::from_str("500").unwrap();
+
+        assert_eq!(NoBidReason::ExchangeSpecific(500), x);
+        assert_eq!("500", serde_json::to_string(&x).unwrap())
+    }
+
+    #[test]
+    fn deserialize_bad() {
+        let serialized = "-1";
+        let res: Result<NoBidReason, serde_json::Error> = serde_json::from_str(serialized);
+
+        assert!(res.is_err())
+    }
+}
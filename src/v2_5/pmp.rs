@@ -6,5 +6,116 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use currency::{self, CurrencyConverter};
+use serde_utils;
+
+use super::bid_request::AuctionType;
+use super::price::Price;
+
+// 3.2.11 Object: Pmp
+//
+// This object is the private marketplace container for direct deals between
+// buyers and sellers that may pertain to this impression.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct PMP {
+    // Only bids for the deals below are accepted when set; otherwise all
+    // bids are.
+    #[serde(
+        default = "serde_utils::default_false",
+        skip_serializing_if = "serde_utils::is_false",
+        serialize_with = "serde_utils::bool_to_u8",
+        deserialize_with = "serde_utils::u8_to_bool"
+    )]
+    pub private_auction: bool,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deals: Vec<Deal>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<serde_utils::Ext>,
+
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
+
+// 3.2.12 Object: Deal
+//
+// A specific deal that was struck a priori between a buyer and a seller.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct PMP {}
+pub struct Deal {
+    pub id: String,
+
+    #[serde(rename = "bidfloor", skip_serializing_if = "Option::is_none")]
+    pub bid_floor: Option<Price>,
+
+    // Defaults to USD.
+    #[serde(rename = "bidfloorcur", skip_serializing_if = "Option::is_none")]
+    pub bid_floor_cur: Option<String>,
+
+    // Overrides BidRequest.at for this deal; 3 means the bid floor is the
+    // agreed deal price.
+    #[serde(rename = "at", skip_serializing_if = "Option::is_none")]
+    pub auction_type: Option<AuctionType>,
+
+    // Buyer seats allowed to bid on this deal.
+    #[serde(rename = "wseat", default, skip_serializing_if = "Vec::is_empty")]
+    pub seat_whitelist: Vec<String>,
+
+    // Advertiser domains allowed to bid on this deal.
+    #[serde(rename = "wadomain", default, skip_serializing_if = "Vec::is_empty")]
+    pub advertiser_whitelist: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<serde_utils::Ext>,
+
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
+
+impl Deal {
+    pub fn new(id: String) -> Deal {
+        Deal {
+            id,
+            bid_floor: None,
+            bid_floor_cur: None,
+            auction_type: None,
+            seat_whitelist: vec![],
+            advertiser_whitelist: vec![],
+            ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
+        }
+    }
+
+    // The deal floor converted to `currency`; bidfloorcur defaults to USD.
+    pub fn floor_in(&self, currency: &str, converter: &dyn CurrencyConverter) -> Result<Option<Price>, currency::Error> {
+        currency::floor_in(self.bid_floor, self.bid_floor_cur.as_deref(), currency, converter)
+    }
+}
+
+impl PMP {
+    pub fn deal(&self, id: &str) -> Option<&Deal> {
+        self.deals.iter().find(|d| d.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn round_trip() {
+        let serialized = r#"{"private_auction":1,"deals":[{"id":"d1","bidfloor":2.5,"at":3,"wseat":["s1"]}]}"#;
+        let pmp: PMP = serde_json::from_str(serialized).unwrap();
+
+        assert!(pmp.private_auction);
+        let deal = pmp.deal("d1").unwrap();
+        assert_eq!(Some(AuctionType::ExchangeSpecific(3)), deal.auction_type);
+        assert_eq!(vec!["s1".to_string()], deal.seat_whitelist);
+        assert_eq!(serialized, serde_json::to_string(&pmp).unwrap());
+        assert_eq!("{}", serde_json::to_string(&PMP::default()).unwrap());
+    }
+}
@@ -0,0 +1,356 @@
+// Copyright (c) 2018 The openrtb-rust authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::error;
+use std::fmt::{self, Display};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use serde;
+
+const MICROS: i64 = 1_000_000;
+const DIGITS: usize = 6;
+
+// An amount of money in millionths of a currency unit, CPM for bid prices
+// and floors. JSON numbers are read to the nearest micro, which is exact
+// for prices under a billion, and written back as the same decimal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Price(i64);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PriceError {
+    // Not a decimal number.
+    Invalid(String),
+    // Beyond what fits in an i64 of micros.
+    OutOfRange(String),
+}
+
+impl Display for PriceError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PriceError::Invalid(ref s) => write!(formatter, "invalid price {:?}", s),
+            PriceError::OutOfRange(ref s) => write!(formatter, "price {} out of range", s),
+        }
+    }
+}
+
+impl error::Error for PriceError {}
+
+impl Price {
+    pub const ZERO: Price = Price(0);
+
+    pub fn from_micros(micros: i64) -> Price {
+        Price(micros)
+    }
+
+    pub fn micros(self) -> i64 {
+        self.0
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / MICROS as f64
+    }
+
+    // Multiplies by a ratio such as an exchange rate or a bid adjustment,
+    // rounding to the nearest micro. Saturates like the f64 conversion;
+    // see `checked_scale`.
+    pub fn scale(self, factor: f64) -> Price {
+        Price((self.0 as f64 * factor).round() as i64)
+    }
+
+    // As `scale`, but None if the factor isn't finite or the result is out
+    // of range.
+    pub fn checked_scale(self, factor: f64) -> Option<Price> {
+        Price::checked_from_micros(self.0 as f64 * factor)
+    }
+
+    // None on overflow, like the integer methods of the same names. The
+    // operators panic instead.
+    pub fn checked_add(self, other: Price) -> Option<Price> {
+        self.0.checked_add(other.0).map(Price)
+    }
+
+    pub fn checked_sub(self, other: Price) -> Option<Price> {
+        self.0.checked_sub(other.0).map(Price)
+    }
+
+    pub fn checked_neg(self) -> Option<Price> {
+        self.0.checked_neg().map(Price)
+    }
+
+    pub fn checked_mul(self, n: i64) -> Option<Price> {
+        self.0.checked_mul(n).map(Price)
+    }
+
+    fn checked_from_f64(value: f64) -> Option<Price> {
+        Price::checked_from_micros(value * MICROS as f64)
+    }
+
+    fn checked_from_micros(micros: f64) -> Option<Price> {
+        let micros = micros.round();
+        if micros.is_finite() && micros >= i64::MIN as f64 && micros < i64::MAX as f64 {
+            Some(Price(micros as i64))
+        } else {
+            None
+        }
+    }
+}
+
+// The float compatibility layer. Converting from f64 rounds to the nearest
+// micro and saturates; NaN becomes zero.
+impl From<f64> for Price {
+    fn from(value: f64) -> Price {
+        Price((value * MICROS as f64).round() as i64)
+    }
+}
+
+impl From<Price> for f64 {
+    fn from(price: Price) -> f64 {
+        price.to_f64()
+    }
+}
+
+// The shortest decimal: "2", "0.1", "-1.000001".
+impl Display for Price {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let micros = self.0.unsigned_abs();
+        let (units, fraction) = (micros / MICROS as u64, micros % MICROS as u64);
+        if fraction == 0 {
+            return write!(formatter, "{}{}", sign, units);
+        }
+        let fraction = format!("{:01$}", fraction, DIGITS);
+        write!(formatter, "{}{}.{}", sign, units, fraction.trim_end_matches('0'))
+    }
+}
+
+// Decimals are read exactly, rounding half away from zero past the sixth
+// fractional digit; exponent forms go through f64.
+impl FromStr for Price {
+    type Err = PriceError;
+
+    fn from_str(s: &str) -> Result<Price, PriceError> {
+        let invalid = || PriceError::Invalid(s.to_string());
+        let out_of_range = || PriceError::OutOfRange(s.to_string());
+
+        if s.contains(['e', 'E']) {
+            let value: f64 = s.parse().map_err(|_| invalid())?;
+            return Price::checked_from_f64(value).ok_or_else(out_of_range);
+        }
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (units, fraction) = match digits.find('.') {
+            Some(i) => (&digits[..i], &digits[i + 1..]),
+            None => (digits, ""),
+        };
+        if (units.is_empty() && fraction.is_empty()) || !units.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let mut micros: i64 = 0;
+        for b in units.bytes().chain(fraction.bytes().chain(std::iter::repeat(b'0')).take(DIGITS)) {
+            micros = micros
+                .checked_mul(10)
+                .and_then(|m| m.checked_add(i64::from(b - b'0')))
+                .ok_or_else(out_of_range)?;
+        }
+        if fraction.as_bytes().get(DIGITS).is_some_and(|&b| b >= b'5') {
+            micros = micros.checked_add(1).ok_or_else(out_of_range)?;
+        }
+        Ok(Price(if negative { -micros } else { micros }))
+    }
+}
+
+// The operators panic on overflow in release builds too, as a wrapped
+// price would be silently wrong; see the checked methods.
+impl Add for Price {
+    type Output = Price;
+
+    fn add(self, other: Price) -> Price {
+        self.checked_add(other).expect("price overflow")
+    }
+}
+
+impl Sub for Price {
+    type Output = Price;
+
+    fn sub(self, other: Price) -> Price {
+        self.checked_sub(other).expect("price overflow")
+    }
+}
+
+impl Neg for Price {
+    type Output = Price;
+
+    fn neg(self) -> Price {
+        self.checked_neg().expect("price overflow")
+    }
+}
+
+impl Mul<i64> for Price {
+    type Output = Price;
+
+    fn mul(self, n: i64) -> Price {
+        self.checked_mul(n).expect("price overflow")
+    }
+}
+
+impl AddAssign for Price {
+    fn add_assign(&mut self, other: Price) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Price {
+    fn sub_assign(&mut self, other: Price) {
+        *self = *self - other;
+    }
+}
+
+impl Sum for Price {
+    fn sum<I: Iterator<Item = Price>>(iter: I) -> Price {
+        iter.fold(Price::ZERO, Add::add)
+    }
+}
+
+impl serde::Serialize for Price {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        // Correctly rounded, so the shortest representation of the f64 is
+        // the decimal itself.
+        serializer.serialize_f64(self.to_f64())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Price {
+    fn deserialize<D>(deserializer: D) -> Result<Price, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_f64(PriceVisitor)
+    }
+}
+
+struct PriceVisitor;
+
+impl<'de> serde::de::Visitor<'de> for PriceVisitor {
+    type Value = Price;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a decimal price")
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Price, E>
+    where
+        E: serde::de::Error,
+    {
+        value
+            .checked_mul(MICROS)
+            .map(Price)
+            .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Signed(value), &self))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Price, E>
+    where
+        E: serde::de::Error,
+    {
+        if value > i64::MAX as u64 {
+            return Err(E::invalid_value(serde::de::Unexpected::Unsigned(value), &self));
+        }
+        self.visit_i64(value as i64)
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Price, E>
+    where
+        E: serde::de::Error,
+    {
+        Price::checked_from_f64(value).ok_or_else(|| E::invalid_value(serde::de::Unexpected::Float(value), &self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn json_round_trip() {
+        for s in &["0.1", "2.5", "0.000123", "1234567.891234", "-0.3", "999999999.999999"] {
+            let price: Price = serde_json::from_str(s).unwrap();
+            assert_eq!(*s, serde_json::to_string(&price).unwrap());
+            assert_eq!(*s, price.to_string());
+        }
+
+        assert_eq!(Price::from_micros(100_000), serde_json::from_str("0.1").unwrap());
+        assert_eq!(Price::from_micros(2_000_000), serde_json::from_str("2").unwrap());
+        assert_eq!(Price::from_micros(1), serde_json::from_str("1e-6").unwrap());
+        assert_eq!("1e-6", serde_json::to_string(&Price::from_micros(1)).unwrap());
+        assert_eq!("2.0", serde_json::to_string(&Price::from_micros(2_000_000)).unwrap());
+        assert!(serde_json::from_str::<Price>("1e300").is_err());
+        assert!(serde_json::from_str::<Price>("\"1.5\"").is_err());
+        assert!(serde_json::from_str::<Price>("18446744073709551615").is_err());
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(Ok(Price::from_micros(100_000)), "0.1".parse());
+        assert_eq!(Ok(Price::from_micros(100_000)), ".1".parse());
+        assert_eq!(Ok(Price::from_micros(3_000_000)), "3.".parse());
+        assert_eq!(Ok(Price::from_micros(1)), "0.0000005".parse());
+        assert_eq!(Ok(Price::from_micros(0)), "0.00000049".parse());
+        assert_eq!(Ok(Price::from_micros(-1_250_000)), "-1.25".parse());
+        assert_eq!(Ok(Price::from_micros(1_000)), "1e-3".parse());
+        assert_eq!(Err(PriceError::Invalid(".".to_string())), ".".parse::<Price>());
+        assert_eq!(Err(PriceError::Invalid("1.2.3".to_string())), "1.2.3".parse::<Price>());
+        assert_eq!(
+            Err(PriceError::OutOfRange("99999999999999".to_string())),
+            "99999999999999".parse::<Price>()
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a: Price = "0.1".parse().unwrap();
+        let b: Price = "0.2".parse().unwrap();
+
+        assert_eq!(Price::from_micros(300_000), a + b);
+        assert_eq!(Price::from_micros(-100_000), a - b);
+        assert_eq!(Price::from_micros(300_000), a * 3);
+        assert_eq!(Price::from_micros(300_000), vec![a, b].into_iter().sum());
+        assert_eq!(Price::from_micros(33_333), a.scale(1.0 / 3.0));
+        assert!(a < b);
+        assert_eq!(Price::from_micros(100_000), Price::from(0.1f32 as f64));
+        assert_eq!(0.3, f64::from(a + b));
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        let max = Price::from_micros(i64::MAX);
+
+        assert_eq!(None, max.checked_add(Price::from_micros(1)));
+        assert_eq!(None, Price::from_micros(i64::MIN).checked_sub(Price::from_micros(1)));
+        assert_eq!(None, Price::from_micros(i64::MIN).checked_neg());
+        assert_eq!(None, max.checked_mul(2));
+        assert_eq!(Some(max), max.checked_sub(Price::ZERO));
+        assert_eq!(Some(Price::from_micros(33_333)), Price::from_micros(100_000).checked_scale(1.0 / 3.0));
+        assert_eq!(None, Price::from_micros(1).checked_scale(f64::INFINITY));
+        assert_eq!(None, Price::from_micros(1).checked_scale(f64::NAN));
+        assert_eq!(None, max.checked_scale(2.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "price overflow")]
+    fn overflow_panics() {
+        let _ = vec![Price::from_micros(i64::MAX), Price::from_micros(1)].into_iter().sum::<Price>();
+    }
+}
@@ -6,6 +6,11 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::convert::TryFrom;
+
+use serde_json::Value;
+
+use privacy::{gpp, us_privacy, Gpp, UsPrivacy};
 use serde_utils;
 
 // This object contains any legal, governmental, or industry regulations
@@ -16,7 +21,10 @@ use serde_utils;
 pub struct Regulations {
     // Flag indicating if this request is subject to the COPPA
     // regulations established by the USA FTC, where 0 = no, 1 = yes.
+    // Missing means no, so that `{}`, which is how `false` is written,
+    // reads back.
     #[serde(
+        default = "serde_utils::default_false",
         skip_serializing_if = "serde_utils::is_false",
         serialize_with = "serde_utils::bool_to_u8",
         deserialize_with = "serde_utils::u8_to_bool"
@@ -26,6 +34,58 @@ pub struct Regulations {
     // Placeholder for exchange-specific extensions to OpenRTB.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
+
+impl Regulations {
+    // The GDPR flag from regs.ext.gdpr, sent as 0/1 or as a boolean.
+    pub fn gdpr(&self) -> Option<bool> {
+        match self.ext.as_ref().and_then(|ext| ext.get("gdpr")) {
+            Some(Value::Bool(b)) => Some(*b),
+            Some(Value::Number(n)) => n.as_u64().map(|n| n == 1),
+            _ => None,
+        }
+    }
+
+    // The CCPA string from regs.ext.us_privacy, decoded.
+    pub fn us_privacy(&self) -> Option<Result<UsPrivacy, us_privacy::Error>> {
+        self.ext_str("us_privacy").map(str::parse)
+    }
+
+    // The Global Privacy Platform string from regs.ext.gpp, decoded.
+    pub fn gpp(&self) -> Option<Result<Gpp, gpp::Error>> {
+        self.ext_str("gpp").map(str::parse)
+    }
+
+    // The GPP section IDs that apply, from regs.ext.gpp_sid. An error if
+    // any entry isn't an integer from 0 to 65535.
+    pub fn gpp_sid(&self) -> Option<Result<Vec<u16>, gpp::Error>> {
+        let ids = self.ext.as_ref().and_then(|ext| ext.get("gpp_sid"))?;
+        let ids = match ids.as_array() {
+            Some(ids) => ids,
+            None => return Some(Err(gpp::Error::InvalidSid(ids.to_string()))),
+        };
+        Some(
+            ids.iter()
+                .map(|id| {
+                    id.as_u64()
+                        .and_then(|id| u16::try_from(id).ok())
+                        .ok_or_else(|| gpp::Error::InvalidSid(id.to_string()))
+                })
+                .collect(),
+        )
+    }
+
+    fn ext_str(&self, key: &str) -> Option<&str> {
+        self.ext
+            .as_ref()
+            .and_then(|ext| ext.get(key))
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+    }
 }
 
 #[cfg(test)]
@@ -38,11 +98,30 @@ mod tests {
         let r = Regulations {
             coppa: false,
             ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
         };
 
         let expected = r#"{}"#;
         let serialized = serde_json::to_string(&r).unwrap();
 
-        assert_eq!(expected, serialized)
+        assert_eq!(expected, serialized);
+        assert_eq!(r, serde_json::from_str(&serialized).unwrap())
+    }
+
+    #[test]
+    fn gpp_sid() {
+        let regs = |s: &str| serde_json::from_str::<Regulations>(s).unwrap();
+
+        assert_eq!(None, regs(r#"{"ext": {}}"#).gpp_sid());
+        assert_eq!(Some(Ok(vec![2, 7])), regs(r#"{"ext": {"gpp_sid": [2, 7]}}"#).gpp_sid());
+        assert_eq!(
+            Some(Err(gpp::Error::InvalidSid("65543".to_string()))),
+            regs(r#"{"ext": {"gpp_sid": [2, 65543]}}"#).gpp_sid()
+        );
+        assert_eq!(
+            Some(Err(gpp::Error::InvalidSid("7".to_string()))),
+            regs(r#"{"ext": {"gpp_sid": 7}}"#).gpp_sid()
+        );
     }
 }
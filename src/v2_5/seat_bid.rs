@@ -1,7 +1,7 @@
 use super::bid::Bid;
 use serde_utils;
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct SeatBid {
     pub bid: Vec<Bid>, // todo: require 1+ bid somehow
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -10,6 +10,9 @@ pub struct SeatBid {
     pub group: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
 
 impl SeatBid {
@@ -19,6 +22,8 @@ impl SeatBid {
             seat: None,
             group: None,
             ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
         }
     }
 }
@@ -6,9 +6,15 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#[cfg(feature = "unknown-fields")]
+use serde_utils;
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Segment {
     // The ID that is associated with this data segment and is specific to the data provider.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
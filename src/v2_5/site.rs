@@ -6,5 +6,100 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct Site {}
+use super::category::Category;
+use super::publisher::Publisher;
+use serde_utils;
+
+// This object should be included if the ad supported content is a website as
+// opposed to a non-browser application.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Site {
+    // Exchange-specific site ID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    // Domain of the site (e.g., "mysite.foo.com").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cat: Vec<Category>,
+
+    #[serde(rename = "sectioncat", default, skip_serializing_if = "Vec::is_empty")]
+    pub section_cat: Vec<Category>,
+
+    #[serde(rename = "pagecat", default, skip_serializing_if = "Vec::is_empty")]
+    pub page_cat: Vec<Category>,
+
+    // URL of the page where the impression will be shown.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<String>,
+
+    // Referrer URL that caused navigation to the current page.
+    #[serde(rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub referrer: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+
+    // Indicates if the site has been programmed to optimize layout when
+    // viewed on mobile devices.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serde_utils::mbool_to_u8",
+        deserialize_with = "serde_utils::u8_to_mbool"
+    )]
+    pub mobile: Option<bool>,
+
+    #[serde(
+        rename = "privacypolicy",
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serde_utils::mbool_to_u8",
+        deserialize_with = "serde_utils::u8_to_mbool"
+    )]
+    pub privacy_policy: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<Publisher>,
+
+    // TODO: add properly
+    // #[skip_serializing_if = "Option::is_none"]
+    // content: Option<Content>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<serde_utils::Ext>,
+
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn serialization_skip_fields() {
+        let serialized = r#"{
+            "id": "102855",
+            "domain": "www.foobar.com",
+            "page": "http://www.foobar.com/1234.html",
+            "mobile": 1,
+            "publisher": {"id": "8953", "name": "foobar.com"}
+        }"#;
+
+        let s: Site = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(Some("www.foobar.com"), s.domain.as_deref());
+        assert_eq!(Some(true), s.mobile);
+        assert_eq!("8953", s.publisher.unwrap().id);
+    }
+}
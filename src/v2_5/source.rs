@@ -6,6 +6,9 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use serde_json;
+
+use super::supply_chain::SupplyChain;
 use serde_utils;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -21,12 +24,35 @@ pub struct Source {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
+
+impl Source {
+    // The supply chain, which OpenRTB 2.5 carries in source.ext.schain.
+    pub fn supply_chain(&self) -> Option<Result<SupplyChain, serde_json::Error>> {
+        self.ext
+            .as_ref()
+            .and_then(|ext| ext.get("schain"))
+            .map(|v| serde_json::from_value(v.clone()))
+    }
+
+    pub fn set_supply_chain(&mut self, schain: &SupplyChain) -> Result<(), serde_json::Error> {
+        let v = serde_json::to_value(schain)?;
+        self.ext
+            .get_or_insert_with(serde_utils::Ext::new)
+            .insert("schain".to_string(), v);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json;
+    use v2_5::SupplyChainNode;
 
     #[test]
     fn serialization_skip_fields() {
@@ -35,6 +61,8 @@ mod tests {
             tid: None,
             pchain: None,
             ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
         };
 
         let expected = r#"{}"#;
@@ -42,4 +70,18 @@ mod tests {
 
         assert_eq!(expected, serialized)
     }
+
+    #[test]
+    fn forward_supply_chain() {
+        let serialized = r#"{"ext":{"schain":{"complete":1,"ver":"1.0","nodes":[{"asi":"exchange1.com","sid":"1234","hp":1}]}}}"#;
+        let mut s: Source = serde_json::from_str(serialized).unwrap();
+
+        let mut schain = s.supply_chain().unwrap().unwrap();
+        schain
+            .append_node(SupplyChainNode::new("reseller.com".to_string(), "42".to_string()))
+            .unwrap();
+        s.set_supply_chain(&schain).unwrap();
+
+        assert_eq!(2, s.supply_chain().unwrap().unwrap().nodes.len());
+    }
 }
@@ -6,6 +6,11 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use serde_json;
+
 use serde_utils;
 
 // This object represents both the links in the supply chain as well
@@ -41,6 +46,10 @@ pub struct SupplyChain {
     // Placeholder for advertising-system specific extensions to this object.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
 
 // This object is associated with a SupplyChain object as an array of nodes.
@@ -103,6 +112,231 @@ pub struct SupplyChainNode {
     // Placeholder for advertising-system specific extensions to this object.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SupplyChainError {
+    // Only version "1.0" of the spec is supported.
+    UnsupportedVersion(String),
+    // The asi of the node at this index isn't a domain name.
+    InvalidAsi(usize, String),
+    // The sid of the node at this index is empty.
+    EmptySid(usize),
+    // The hp of the node at this index isn't 0 or 1.
+    InvalidHp(usize, String),
+    // The compact string form couldn't be parsed.
+    Malformed(String),
+}
+
+impl Display for SupplyChainError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SupplyChainError::UnsupportedVersion(ref v) => {
+                write!(formatter, "unsupported schain version {}", v)
+            }
+            SupplyChainError::InvalidAsi(i, ref asi) => {
+                write!(formatter, "node {}: asi {} is not a domain", i, asi)
+            }
+            SupplyChainError::EmptySid(i) => write!(formatter, "node {}: empty sid", i),
+            SupplyChainError::InvalidHp(i, ref hp) => {
+                write!(formatter, "node {}: hp must be 0 or 1, got {}", i, hp)
+            }
+            SupplyChainError::Malformed(ref s) => write!(formatter, "malformed schain: {}", s),
+        }
+    }
+}
+
+impl SupplyChain {
+    pub fn new(complete: bool) -> SupplyChain {
+        SupplyChain {
+            complete,
+            nodes: vec![],
+            ver: "1.0".to_string(),
+            ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
+        }
+    }
+
+    // Adds a node for the entity forwarding the request. The node must be
+    // valid on its own; the chain's completeness is left as it is.
+    pub fn append_node(&mut self, node: SupplyChainNode) -> Result<(), SupplyChainError> {
+        node.validate(self.nodes.len())?;
+        self.nodes.push(node);
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), SupplyChainError> {
+        if self.ver != "1.0" {
+            return Err(SupplyChainError::UnsupportedVersion(self.ver.clone()));
+        }
+        for (i, node) in self.nodes.iter().enumerate() {
+            node.validate(i)?;
+        }
+        Ok(())
+    }
+}
+
+impl SupplyChainNode {
+    pub fn new(asi: String, sid: String) -> SupplyChainNode {
+        SupplyChainNode {
+            asi,
+            sid,
+            rid: None,
+            name: None,
+            domain: None,
+            hp: true,
+            ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
+        }
+    }
+
+    fn validate(&self, index: usize) -> Result<(), SupplyChainError> {
+        if !is_domain(&self.asi) {
+            return Err(SupplyChainError::InvalidAsi(index, self.asi.clone()));
+        }
+        if self.sid.is_empty() {
+            return Err(SupplyChainError::EmptySid(index));
+        }
+        Ok(())
+    }
+}
+
+// Compact form used in HTTP headers and Prebid configs:
+// "{ver},{complete}!{asi},{sid},{hp},{rid},{name},{domain},{ext}!...",
+// with values URL-encoded and trailing empty node fields left out.
+impl Display for SupplyChain {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{},{}", encode(&self.ver), self.complete as u8)?;
+        for node in &self.nodes {
+            let ext = match node.ext {
+                Some(ref ext) => serde_json::to_string(ext).map_err(|_| fmt::Error)?,
+                None => String::new(),
+            };
+            let mut fields = vec![
+                encode(&node.asi),
+                encode(&node.sid),
+                (node.hp as u8).to_string(),
+                node.rid.as_ref().map(|x| encode(x)).unwrap_or_default(),
+                node.name.as_ref().map(|x| encode(x)).unwrap_or_default(),
+                node.domain.as_ref().map(|x| encode(x)).unwrap_or_default(),
+                encode(&ext),
+            ];
+            while fields.last().map(|f| f.is_empty()) == Some(true) {
+                fields.pop();
+            }
+            write!(formatter, "!{}", fields.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SupplyChain {
+    type Err = SupplyChainError;
+
+    fn from_str(s: &str) -> Result<SupplyChain, SupplyChainError> {
+        let malformed = |what: &str| SupplyChainError::Malformed(what.to_string());
+
+        let mut parts = s.split('!');
+        let header: Vec<&str> = parts.next().unwrap_or_default().split(',').collect();
+        if header.len() != 2 {
+            return Err(malformed("expected \"ver,complete\""));
+        }
+
+        let mut chain = SupplyChain::new(match header[1] {
+            "0" => false,
+            "1" => true,
+            _ => return Err(malformed("complete must be 0 or 1")),
+        });
+        chain.ver = decode(header[0])?;
+
+        for (i, part) in parts.enumerate() {
+            let fields = part
+                .split(',')
+                .map(decode)
+                .collect::<Result<Vec<String>, SupplyChainError>>()?;
+            if fields.len() < 3 || fields.len() > 7 {
+                return Err(malformed("a node has 3 to 7 fields"));
+            }
+            let optional = |n: usize| fields.get(n).filter(|f| !f.is_empty()).cloned();
+
+            let mut node = SupplyChainNode::new(fields[0].clone(), fields[1].clone());
+            node.hp = match fields[2].as_str() {
+                "0" => false,
+                "1" => true,
+                hp => return Err(SupplyChainError::InvalidHp(i, hp.to_string())),
+            };
+            node.rid = optional(3);
+            node.name = optional(4);
+            node.domain = optional(5);
+            node.ext = match optional(6) {
+                Some(ext) => Some(serde_json::from_str(&ext).map_err(|e| {
+                    SupplyChainError::Malformed(format!("node {}: ext: {}", i, e))
+                })?),
+                None => None,
+            };
+            chain.nodes.push(node);
+        }
+
+        chain.validate()?;
+        Ok(chain)
+    }
+}
+
+fn is_domain(s: &str) -> bool {
+    let labels: Vec<&str> = s.split('.').collect();
+    labels.len() >= 2
+        && labels.iter().all(|l| {
+            !l.is_empty()
+                && l.len() <= 63
+                && !l.starts_with('-')
+                && !l.ends_with('-')
+                && l.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+        && labels
+            .last()
+            .map(|tld| tld.chars().all(|c| c.is_ascii_alphabetic()))
+            == Some(true)
+}
+
+// Percent-encodes like JavaScript's encodeURIComponent, except that '!' is
+// encoded too since it separates nodes.
+fn encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'*' | b'\'' | b'(' | b')' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+fn decode(s: &str) -> Result<String, SupplyChainError> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s
+                .get(i + 1..i + 3)
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+                .ok_or_else(|| SupplyChainError::Malformed(format!("bad escape in {}", s)))?;
+            out.push(hex);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).map_err(|_| SupplyChainError::Malformed(format!("bad UTF-8 in {}", s)))
 }
 
 #[test]
@@ -117,12 +351,73 @@ fn serialization_skip_fields() {
             rid: Some("BidRequest1".to_string()),
             hp: true,
             ext: None,
+            #[cfg(feature = "unknown-fields")]
+            other: Default::default(),
         }],
         ver: "1.0".to_string(),
         ext: None,
+        #[cfg(feature = "unknown-fields")]
+        other: Default::default(),
     };
     let expected = r#"{"complete":1,"nodes":[{"asi":"directseller.com","sid":"00001","rid":"BidRequest1","hp":1}],"ver":"1.0"}"#;
     let serialized = serde_json::to_string(&s).unwrap();
 
     assert_eq!(expected, serialized)
 }
+
+#[test]
+fn compact_round_trip() {
+    let compact = "1.0,1!exchange1.com,1234%21abcd,1,bid-request-1,publisher%2C%20Inc.,publisher.com!exchange2.com,abcd,1";
+    let s: SupplyChain = compact.parse().unwrap();
+
+    assert!(s.complete);
+    assert_eq!("1234!abcd", s.nodes[0].sid);
+    assert_eq!(Some("publisher, Inc.".to_string()), s.nodes[0].name);
+    assert_eq!(None, s.nodes[1].rid);
+    assert_eq!(compact, s.to_string());
+}
+
+#[test]
+fn compact_ext() {
+    let mut s = SupplyChain::new(false);
+    let mut node = SupplyChainNode::new("exchange1.com".to_string(), "1234".to_string());
+    node.ext = Some(serde_json::from_str(r#"{"seat":"a,b"}"#).unwrap());
+    s.append_node(node).unwrap();
+
+    let compact = s.to_string();
+    assert_eq!("1.0,0!exchange1.com,1234,1,,,,%7B%22seat%22%3A%22a%2Cb%22%7D", compact);
+    assert_eq!(s, compact.parse().unwrap());
+}
+
+#[test]
+fn compact_invalid() {
+    assert_eq!(
+        Err(SupplyChainError::UnsupportedVersion("2.0".to_string())),
+        "2.0,1!exchange1.com,1234,1".parse::<SupplyChain>()
+    );
+    assert_eq!(
+        Err(SupplyChainError::InvalidHp(0, "2".to_string())),
+        "1.0,1!exchange1.com,1234,2".parse::<SupplyChain>()
+    );
+    assert_eq!(
+        Err(SupplyChainError::InvalidAsi(1, "not a domain".to_string())),
+        "1.0,1!exchange1.com,1234,1!not%20a%20domain,1,1".parse::<SupplyChain>()
+    );
+    assert_eq!(
+        Err(SupplyChainError::EmptySid(0)),
+        "1.0,1!exchange1.com,,1".parse::<SupplyChain>()
+    );
+}
+
+#[test]
+fn append_node() {
+    let mut s: SupplyChain = "1.0,1!exchange1.com,1234,1".parse().unwrap();
+
+    assert!(s
+        .append_node(SupplyChainNode::new("reseller.com".to_string(), String::new()))
+        .is_err());
+    s.append_node(SupplyChainNode::new("reseller.com".to_string(), "42".to_string()))
+        .unwrap();
+
+    assert_eq!("1.0,1!exchange1.com,1234,1!reseller.com,42,1", s.to_string());
+}
@@ -6,7 +6,14 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::borrow::Cow;
+
+use serde::Deserialize;
+use serde_json::Value;
+
 use super::data::Data;
+use super::eid::{Eid, ID5_SOURCE, SHARED_ID_SOURCE, UID2_SOURCE};
+use privacy::tcf::{self, TcString};
 use serde_utils;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -23,4 +30,138 @@ pub struct User {
     // The Index static identifier for this user (contains only alphanumeric or the following characters: @ - . _ ).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
+    // Extended identifiers, as placed by OpenRTB 2.6. In 2.5 they're sent in
+    // ext.eids instead; the accessors below read both.
+    // Malformed entries are left out rather than failing the request.
+    #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "serde_utils::skip_invalid")]
+    pub eids: Vec<Eid>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
+}
+
+impl User {
+    // All extended identifiers, from user.eids followed by those in
+    // user.ext.eids whose source isn't already listed. Malformed entries
+    // in ext are skipped.
+    pub fn all_eids(&self) -> Vec<Cow<'_, Eid>> {
+        let mut eids: Vec<Cow<'_, Eid>> = self.eids.iter().map(Cow::Borrowed).collect();
+        for v in self.ext_eids() {
+            if let Ok(eid) = Eid::deserialize(v) {
+                if !eids.iter().any(|e| e.source == eid.source) {
+                    eids.push(Cow::Owned(eid));
+                }
+            }
+        }
+        eids
+    }
+
+    // The extended identifier for a source, e.g. "uidapi.com". Only the
+    // matching entry of user.ext.eids is decoded.
+    pub fn eid_for(&self, source: &str) -> Option<Cow<'_, Eid>> {
+        if let Some(eid) = self.eids.iter().find(|e| e.source == source) {
+            return Some(Cow::Borrowed(eid));
+        }
+        self.ext_eids()
+            .filter(|v| v.get("source").and_then(Value::as_str) == Some(source))
+            .find_map(|v| Eid::deserialize(v).ok())
+            .map(Cow::Owned)
+    }
+
+    // The Unified ID 2.0 token.
+    pub fn uid2(&self) -> Option<String> {
+        self.first_id_for(UID2_SOURCE)
+    }
+
+    // The ID5 ID.
+    pub fn id5(&self) -> Option<String> {
+        self.first_id_for(ID5_SOURCE)
+    }
+
+    // The SharedID (formerly PubCommon ID).
+    pub fn shared_id(&self) -> Option<String> {
+        self.first_id_for(SHARED_ID_SOURCE)
+    }
+
+    // The GDPR consent string from user.ext.consent, decoded. `None` if
+    // there is no consent string.
+    pub fn consent(&self) -> Option<Result<TcString, tcf::Error>> {
+        self.ext
+            .as_ref()
+            .and_then(|ext| ext.get("consent"))
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(str::parse)
+    }
+
+    fn first_id_for(&self, source: &str) -> Option<String> {
+        self.eid_for(source)
+            .and_then(|e| e.first_id().map(|id| id.to_string()))
+    }
+
+    fn ext_eids(&self) -> impl Iterator<Item = &Value> {
+        self.ext
+            .as_ref()
+            .and_then(|ext| ext.get("eids"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+    use v2_5::AgentType;
+
+    #[test]
+    fn eids_from_both_locations() {
+        let serialized = r#"{
+            "id": "1234",
+            "eids": [{"source": "uidapi.com", "uids": [{"id": "uid2-token", "atype": 3}]}],
+            "ext": {
+                "eids": [
+                    {"source": "uidapi.com", "uids": [{"id": "stale"}]},
+                    {"source": "id5-sync.com", "uids": [{"id": "ID5*abc", "atype": 1}]},
+                    {"source": "broken"}
+                ]
+            }
+        }"#;
+        let u: User = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(Some("uid2-token".to_string()), u.uid2());
+        assert_eq!(Some("ID5*abc".to_string()), u.id5());
+        assert_eq!(None, u.shared_id());
+        assert_eq!(
+            Some(AgentType::Device),
+            u.eid_for("id5-sync.com").unwrap().uids[0].atype
+        );
+        assert_eq!(2, u.all_eids().len());
+    }
+
+    #[test]
+    fn malformed_eids_are_skipped() {
+        let serialized = r#"{
+            "eids": [
+                {"source": "adserver.org", "uids": [{"id": "x", "atype": "1"}]},
+                {"source": "uidapi.com", "uids": [{"id": "uid2-token", "atype": 0}]},
+                {"source": "id5-sync.com"}
+            ]
+        }"#;
+        let u: User = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(1, u.eids.len());
+        assert_eq!(Some("uid2-token".to_string()), u.uid2());
+        assert_eq!(Some(AgentType::Reserved(0)), u.eids[0].uids[0].atype);
+    }
+
+    #[test]
+    fn consent() {
+        let u: User = serde_json::from_str(r#"{"ext": {"consent": "BOEFEAyOEFEAyAHABDENAI4AAAB9vABAASA"}}"#).unwrap();
+        assert_eq!(Some(Err(tcf::Error::UnsupportedVersion(1))), u.consent());
+
+        let u: User = serde_json::from_str(r#"{"ext": {"consent": ""}}"#).unwrap();
+        assert_eq!(None, u.consent());
+    }
 }
@@ -84,6 +84,9 @@ pub struct Video {
     pub playbackmethod: Vec<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ext: Option<serde_utils::Ext>,
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten, skip_serializing_if = "serde_utils::Ext::is_empty", serialize_with = "serde_utils::serialize_other")]
+    pub other: serde_utils::Ext,
 }
 
 #[test]
@@ -103,6 +106,8 @@ fn serialization_skip_fields() {
         boxingallowed: None,
         playbackmethod: vec![],
         ext: None,
+        #[cfg(feature = "unknown-fields")]
+        other: Default::default(),
     };
 
     let expected = r#"{"mimes":["video/mp4"]}"#;